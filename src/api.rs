@@ -1,66 +1,111 @@
+use chrono::NaiveDate;
 use tick_cli::{Project, Role, Task, TickEntry, User};
 use crate::config::Config;
-use crate::http::{self, HttpError};
+use crate::error::TickResult;
+use crate::http;
 
-const BASE_URL: &str = "https://secure.tickspot.com";
 const API_VERSION: &str = "v2";
 
-pub fn get_roles(email: &String, password: &String) -> Result<Vec<Role>, HttpError> {
-    let url = format!("{}/api/{}/roles.json", BASE_URL, API_VERSION);
+pub fn get_roles(config: &Config, email: &String, password: &String) -> TickResult<Vec<Role>> {
+    let url = format!("{}/api/{}/roles.json", config.get_base_url(), API_VERSION);
 
-    http::get_basic_auth((email, password), &url)
+    http::get_basic_auth(config, (email, password), &url)
 }
 
-pub fn get_users(config: &Config) -> Result<Vec<User>, HttpError> {
+pub fn get_users(config: &Config) -> TickResult<Vec<User>> {
     let url = format!(
         "{}/{}/api/{}/users.json",
-        BASE_URL,
-        config.get_subscription_id(),
+        config.get_base_url(),
+        config.get_subscription_id()?,
         API_VERSION,
     );
 
     http::get(config, &url)
 }
 
-pub fn get_projects(config: &Config) -> Result<Vec<Project>, HttpError> {
+/// Gets the users of a subscription with a token that isn't in use yet, which
+/// is the one way to check them both.
+pub fn get_users_with_token(config: &Config, subscription_id: u32, api_token: &str) -> TickResult<Vec<User>> {
+    let url = format!(
+        "{}/{}/api/{}/users.json",
+        config.get_base_url(),
+        subscription_id,
+        API_VERSION,
+    );
+
+    http::get_with_token(config, api_token, &url)
+}
+
+pub fn get_projects(config: &Config) -> TickResult<Vec<Project>> {
     let url = format!(
         "{}/{}/api/{}/projects.json",
-        BASE_URL,
-        config.get_subscription_id(),
+        config.get_base_url(),
+        config.get_subscription_id()?,
         API_VERSION,
     );
 
-    http::get(config, &url)
+    http::get_all(config, &url)
 }
 
-pub fn get_tasks(config: &Config, project_id: &u32) -> Result<Vec<Task>, HttpError> {
+pub fn get_tasks(config: &Config, project_id: &u32) -> TickResult<Vec<Task>> {
     let url = format!(
         "{}/{}/api/{}/projects/{}/tasks.json",
-        BASE_URL,
-        config.get_subscription_id(),
+        config.get_base_url(),
+        config.get_subscription_id()?,
         API_VERSION,
         project_id
     );
 
+    http::get_all(config, &url)
+}
+
+pub fn get_task(config: &Config, id: &u32) -> TickResult<Task> {
+    let url = format!(
+        "{}/{}/api/{}/tasks/{}.json",
+        config.get_base_url(),
+        config.get_subscription_id()?,
+        API_VERSION,
+        id
+    );
+
     http::get(config, &url)
 }
 
-pub fn create_entry(config: &Config, entry: &TickEntry) -> Result<TickEntry, HttpError> {
+pub fn get_entries(
+    config: &Config,
+    user_id: &u32,
+    from: &NaiveDate,
+    to: &NaiveDate,
+) -> TickResult<Vec<TickEntry>> {
+    let url = format!(
+        "{}/{}/api/{}/entries.json?user_id={}&start_date={}&end_date={}",
+        config.get_base_url(),
+        config.get_subscription_id()?,
+        API_VERSION,
+        user_id,
+        from.format("%Y-%m-%d"),
+        to.format("%Y-%m-%d"),
+    );
+
+    http::get_all(config, &url)
+}
+
+pub fn create_entry(config: &Config, entry: &TickEntry) -> TickResult<TickEntry> {
     let url = format!(
         "{}/{}/api/{}/entries.json",
-        BASE_URL,
-        config.get_subscription_id(),
+        config.get_base_url(),
+        config.get_subscription_id()?,
         API_VERSION,
     );
 
     http::post(config, &url, entry)
 }
 
-pub fn update_entry(config: &Config, entry: &TickEntry) -> Result<TickEntry, HttpError> {
+pub fn update_entry(config: &Config, entry: &TickEntry) -> TickResult<TickEntry> {
     let url = format!(
         "{}/{}/api/{}/entries/{}.json",
-        BASE_URL,
-        config.get_subscription_id(),
+        config.get_base_url(),
+        config.get_subscription_id()?,
         API_VERSION,
         entry.get_id().unwrap(),
     );
@@ -68,11 +113,11 @@ pub fn update_entry(config: &Config, entry: &TickEntry) -> Result<TickEntry, Htt
     http::put(config, &url, entry)
 }
 
-pub fn delete_entry(config: &Config, id: u32) -> Result<TickEntry, HttpError> {
+pub fn delete_entry(config: &Config, id: u32) -> TickResult<()> {
     let url = format!(
         "{}/{}/api/{}/entries/{}.json",
-        BASE_URL,
-        config.get_subscription_id(),
+        config.get_base_url(),
+        config.get_subscription_id()?,
         API_VERSION,
         id,
     );
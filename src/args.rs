@@ -1,23 +1,383 @@
-use std::error::Error;
+use std::{error::Error, path::PathBuf};
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveTime};
 use clap::{Parser,Subcommand};
+use crate::ui;
+use tick_cli::{EntryKind, RoundingMode};
 
 type ArgResult<T> = Result<T, Box<dyn Error>>;
 
+const ROUNDING_MINUTES: [u32; 4] = [5, 6, 15, 30];
+
 #[derive(Subcommand, Debug)]
 pub enum Command {
-    Login,
+    Login(LoginArgs),
     Logout,
-    Add,
+    /// Manage the Tick subscription in use
+    Account {
+        #[command(subcommand)]
+        command: AccountCommand,
+    },
+    Add(AddArgs),
     List,
     Edit,
-    Submit,
+    Submit(SubmitArgs),
     Delete,
+    /// Check a day for overlapping, inverted and missing times
+    Check(CheckArgs),
+    /// Start a timer for a new entry
+    Start(TimerArgs),
+    /// Stop the running timer and store it as an entry
+    Stop,
+    /// Stop the running timer and start a new one
+    Switch(TimerArgs),
+    /// Import entries made elsewhere in Tick into the local days
+    Pull(PullArgs),
+    /// Show the booked hours over a range of days
+    Report(ReportArgs),
+    /// Export entries over a range of days
+    Export(ExportArgs),
+    /// Import entries from a CSV export of another time tracker
+    Import(ImportArgs),
+    /// Manage how submitted hours are rounded
+    Rounding {
+        #[command(subcommand)]
+        command: RoundingCommand,
+    },
+    /// Manage templates for frequent entries
+    Template {
+        #[command(subcommand)]
+        command: TemplateCommand,
+    },
+    /// Manage profiles for separate Tick accounts
+    Profile {
+        #[command(subcommand)]
+        command: ProfileCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProfileCommand {
+    /// List all profiles
+    List,
+    /// Add a profile
+    Add { name: String },
+    /// Remove a profile and its credentials
+    Remove { name: String },
+    /// Use a profile when none is given
+    Default { name: String },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AccountCommand {
+    /// Switch to another subscription of the logged in account
+    Switch,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RoundingCommand {
+    /// Show the rounding for all projects and for specific ones
+    List,
+    /// Round the hours of all projects, or of one project
+    Set(RoundingArgs),
+    /// Stop rounding the hours of all projects, or of one project
+    Clear {
+        /// Project name, matched exactly or by a unique part of it
+        #[arg(long)]
+        project: Option<String>,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+pub struct RoundingArgs {
+    /// Round to the nearest, up or down
+    #[arg(value_parser = parse_rounding_mode)]
+    pub mode: RoundingMode,
+
+    /// Minutes to round to: 5, 6, 15 or 30
+    #[arg(value_parser = parse_rounding_minutes)]
+    pub minutes: u32,
+
+    /// Project name, matched exactly or by a unique part of it
+    #[arg(long)]
+    pub project: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TemplateCommand {
+    /// Add a template, or replace the one with the same name
+    Add(TemplateArgs),
+    /// List all templates
+    List,
+    /// Remove a template
+    Remove { name: String },
+}
+
+#[derive(clap::Args, Debug)]
+pub struct TemplateArgs {
+    pub name: String,
+
+    /// Project name, matched exactly or by a unique part of it
+    #[arg(long)]
+    pub project: Option<String>,
+
+    /// Task name, matched exactly or by a unique part of it
+    #[arg(long, requires = "project")]
+    pub task: Option<String>,
+
+    /// Notes describing the work
+    #[arg(long, default_value = "")]
+    pub notes: String,
+
+    /// Length of the entries, like 15m, 1h or 1h30m
+    #[arg(long, value_parser = parse_duration)]
+    pub duration: Option<i64>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct AddArgs {
+    /// Kind of entry: work, break or personal. Only work is submitted
+    #[arg(long, value_parser = parse_kind, conflicts_with_all = ["template", "project", "task"])]
+    pub kind: Option<EntryKind>,
+
+    /// Template to take the project, task, notes and duration from
+    #[arg(long, conflicts_with_all = ["project", "task"])]
+    pub template: Option<String>,
+
+    /// Date of the entry, like 2023-01-30, yesterday, fri, last fri or -2d
+    #[arg(long, value_parser = parse_date)]
+    pub date: Option<NaiveDate>,
+
+    /// Project name, matched exactly or by a unique part of it
+    #[arg(long)]
+    pub project: Option<String>,
+
+    /// Task name, matched exactly or by a unique part of it
+    #[arg(long, requires = "project")]
+    pub task: Option<String>,
+
+    /// Start time, like 9:15, 930, 9:30pm, now or -15m
+    #[arg(long, value_parser = parse_time)]
+    pub start: Option<NaiveTime>,
+
+    /// End time, like 17:30, 1730, 5:30pm, now, +1h or 01:30+1 for the next day
+    #[arg(long, value_parser = parse_end_time)]
+    pub end: Option<(NaiveTime, bool)>,
+
+    /// Hours of work without start and end times, like 2.5 or 1h30m
+    #[arg(long, value_parser = parse_hours, conflicts_with_all = ["start", "end"])]
+    pub hours: Option<f64>,
+
+    /// Notes describing the work
+    #[arg(long)]
+    pub notes: Option<String>,
+
+    /// Don't ask for confirmation, and leave the end time and notes
+    /// empty when they aren't given
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct TimerArgs {
+    /// Kind of entry: work, break or personal. Only work is submitted
+    #[arg(long, value_parser = parse_kind, conflicts_with_all = ["template", "project", "task"])]
+    pub kind: Option<EntryKind>,
+
+    /// Template to take the project, task and notes from
+    #[arg(long, conflicts_with_all = ["project", "task"])]
+    pub template: Option<String>,
+
+    /// Project name, matched exactly or by a unique part of it
+    #[arg(long)]
+    pub project: Option<String>,
+
+    /// Task name, matched exactly or by a unique part of it
+    #[arg(long, requires = "project")]
+    pub task: Option<String>,
+
+    /// Notes describing the work
+    #[arg(long)]
+    pub notes: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct PullArgs {
+    #[command(flatten)]
+    pub range: RangeArgs,
+
+    /// Id of the Tick user to pull the entries of, when the subscription has
+    /// several. Defaults to the configured user_id
+    #[arg(long)]
+    pub user: Option<u32>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SubmitArgs {
+    /// Submit even when entries overlap or end before they start
+    #[arg(long)]
+    pub force: bool,
+
+    #[command(flatten)]
+    pub range: RangeArgs,
+
+    /// Every day with entries that still have to be submitted
+    #[arg(long, conflicts_with_all = ["week", "month", "from", "to"])]
+    pub all_pending: bool,
+
+    /// Only show what would be created and updated in Tick
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Don't ask for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct LoginArgs {
+    /// Log in with an API token from your Tick profile instead of a password
+    #[arg(long, requires = "subscription")]
+    pub token: Option<String>,
+
+    /// The subscription the token belongs to
+    #[arg(long, requires = "token")]
+    pub subscription: Option<u32>,
 }
 
+#[derive(clap::Args, Debug)]
+pub struct CheckArgs {
+    /// Date to check, like 2023-01-30 or yesterday. Defaults to today
+    #[arg(value_parser = parse_date)]
+    pub date: Option<NaiveDate>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct RangeArgs {
+    /// The current week, from Monday to Sunday
+    #[arg(long, conflicts_with_all = ["month", "from", "to"])]
+    pub week: bool,
+
+    /// The current month
+    #[arg(long, conflicts_with_all = ["from", "to"])]
+    pub month: bool,
+
+    /// First date of the range, like 2023-01-30 or last mon. Defaults to today
+    #[arg(long, value_parser = parse_date)]
+    pub from: Option<NaiveDate>,
+
+    /// Last date of the range, like 2023-01-30 or fri. Defaults to the first date
+    #[arg(long, value_parser = parse_date)]
+    pub to: Option<NaiveDate>,
+}
+
+impl RangeArgs {
+    pub fn is_given(&self) -> bool {
+        self.week || self.month || self.from.is_some() || self.to.is_some()
+    }
+
+    pub fn range(&self) -> (NaiveDate, NaiveDate) {
+        let today = ui::today();
+
+        if self.week {
+            let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+            return (monday, monday + Duration::days(6));
+        }
+
+        if self.month {
+            let first = today.with_day(1).unwrap();
+            let next_month = first.checked_add_months(Months::new(1)).unwrap();
+            return (first, next_month.pred_opt().unwrap());
+        }
+
+        let from = self.from.unwrap_or(today);
+        let to = self.to.unwrap_or(from);
+
+        (from, to)
+    }
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ReportArgs {
+    #[command(flatten)]
+    pub range: RangeArgs,
+
+    /// How to group the hours
+    #[arg(long, value_enum, default_value_t = GroupBy::Project)]
+    pub by: GroupBy,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum GroupBy {
+    Project,
+    Task,
+    Day,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ExportArgs {
+    #[command(flatten)]
+    pub range: RangeArgs,
+
+    #[arg(long, value_enum)]
+    pub format: ExportFormat,
+
+    /// File to write to instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Ics,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ImportArgs {
+    /// CSV file to import
+    pub file: PathBuf,
+
+    #[arg(long, value_enum, default_value_t = ImportFormat::Csv)]
+    pub format: ImportFormat,
+
+    /// Read a field from another column, like project=Client. Fields are
+    /// date, start, end, duration, project, task and notes
+    #[arg(long = "column", value_parser = parse_column)]
+    pub columns: Vec<(String, String)>,
+
+    /// How dates are written, like %d/%m/%Y. Needed for dates like 03/05/2024,
+    /// which could be either day or month first
+    #[arg(long)]
+    pub date_format: Option<String>,
+
+    /// Only show what would be imported
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Don't ask for confirmation, and import unknown projects and tasks
+    /// without one
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ImportFormat {
+    TogglCsv,
+    ClockifyCsv,
+    Csv,
+}
+
+pub const IMPORT_FIELDS: [&str; 7] = ["date", "start", "end", "duration", "project", "task", "notes"];
+
 #[derive(Parser, Debug)]
 #[command(bin_name = "tick")]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
+    /// Profile to use instead of the default one
+    #[arg(long, global = true, env = "TICK_PROFILE")]
+    pub profile: Option<String>,
+
     #[command(subcommand)]
     pub command: Command,
 }
@@ -25,3 +385,51 @@ pub struct Args {
 pub fn get() -> ArgResult<Args> {
     Ok(Args::parse())
 }
+
+fn parse_date(input: &str) -> Result<NaiveDate, String> {
+    ui::parse_date(input).ok_or_else(|| ui::DATE_FORMAT_ERROR.to_string())
+}
+
+fn parse_time(input: &str) -> Result<NaiveTime, String> {
+    ui::parse_time(input, None).ok_or_else(|| ui::TIME_FORMAT_ERROR.to_string())
+}
+
+fn parse_end_time(input: &str) -> Result<(NaiveTime, bool), String> {
+    ui::parse_end_time(input, None).ok_or_else(|| ui::TIME_FORMAT_ERROR.to_string())
+}
+
+fn parse_rounding_mode(input: &str) -> Result<RoundingMode, String> {
+    input.parse()
+}
+
+fn parse_rounding_minutes(input: &str) -> Result<u32, String> {
+    match input.parse() {
+        Ok(minutes) if ROUNDING_MINUTES.contains(&minutes) => Ok(minutes),
+        _ => Err("Please round to 5, 6, 15 or 30 minutes".to_string()),
+    }
+}
+
+fn parse_hours(input: &str) -> Result<f64, String> {
+    tick_cli::parse_hours(input)
+        .ok_or_else(|| "Wrong hours format. Please provide hours like 2.5 or 1h30m, up to 24".to_string())
+}
+
+fn parse_kind(input: &str) -> Result<EntryKind, String> {
+    input.parse()
+}
+
+/// Parses durations like 15m, 2h, 1h30m or 1.5h into minutes.
+fn parse_duration(input: &str) -> Result<i64, String> {
+    tick_cli::parse_duration(input)
+        .map(|duration| duration.num_minutes())
+        .ok_or_else(|| "Wrong duration format. Please provide a duration like 15m or 1h30m".to_string())
+}
+
+fn parse_column(input: &str) -> Result<(String, String), String> {
+    match input.split_once('=') {
+        Some((field, column)) if IMPORT_FIELDS.contains(&field) => {
+            Ok((field.to_string(), column.to_string()))
+        }
+        _ => Err(format!("Expected field=Column, with field one of {}", IMPORT_FIELDS.join(", "))),
+    }
+}
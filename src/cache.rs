@@ -1,6 +1,7 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::HashMap, marker::PhantomData, path::PathBuf};
 use serde::{Serialize, Deserialize, de::DeserializeOwned};
-use crate::files;
+use serde_json::Value;
+use crate::files::{self, FileError};
 
 const FILENAME: &str = "cache.json";
 
@@ -29,34 +30,52 @@ impl<T> CachedResponse<T> {
     }
 }
 
+/// Responses of every type share one cache file, so they're kept as plain
+/// json and only converted to `T` when read.
 pub struct Cache<T> {
-    map: HashMap<String, CachedResponse<T>>,
+    map: HashMap<String, CachedResponse<Value>>,
+    response_type: PhantomData<T>,
 }
 
 impl<T: DeserializeOwned + Serialize + Clone> Cache<T> {
     pub fn new() -> Self {
-        let map = match files::read_from_cache(&get_path()) {
+        let map = match get_path().and_then(|path| files::read_from_cache(&path)) {
             Ok(content) => serde_json::from_str(&content).unwrap_or_else(|_| HashMap::new()), 
             Err(_) => HashMap::new()
         };
 
-        Cache { map }
+        Cache { map, response_type: PhantomData }
     }
 
     pub fn get(&self, key: String) -> Option<CachedResponse<T>> {
-        self.map.get(&key).cloned()
+        let cached_response = self.map.get(&key)?;
+        let json = serde_json::from_value(cached_response.json.clone()).ok()?;
+
+        Some(CachedResponse::new(
+            cached_response.etag.clone(),
+            cached_response.last_modified.clone(),
+            json,
+        ))
     }
 
     pub fn set(&mut self, key: String, cached_response: CachedResponse<T>) {
-        self.map.insert(key, cached_response);
+        let json = serde_json::to_value(&cached_response.json).unwrap();
+
+        self.map.insert(key, CachedResponse::new(
+            cached_response.etag,
+            cached_response.last_modified,
+            json,
+        ));
 
-        files::write_to_cache(
-            &get_path(),
+        // The cache only saves requests, so failing to write it is no reason
+        // to fail the command.
+        let _ = get_path().and_then(|path| files::write_to_cache(
+            &path,
             serde_json::to_string(&self.map).unwrap(),
-        ).expect("Failed to write to cache file");
+        ));
     }
 }
 
-fn get_path() -> PathBuf {
+fn get_path() -> Result<PathBuf, FileError> {
     files::get_cache_file_path(None, Some(&FILENAME.to_string()))
 }
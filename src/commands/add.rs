@@ -1,125 +1,347 @@
-use std::process;
 use crate::{api, ui, files};
+use crate::error::{TickError, TickResult};
+use crate::args::AddArgs;
 use crate::config::Config;
 use crate::repository;
-use chrono::{NaiveDate, NaiveTime, Utc};
-use tick_cli::{Entry, EntryList, Project, Task};
+use super::check::warn_about_problems;
+use chrono::{NaiveDate, NaiveTime};
+use dialoguer::console::style;
+use tick_cli::{Entry, EntryKind, EntryList, NameMatchError, Project, Task, Template};
 
-pub fn add_entry(config: &Config) -> std::io::Result<()> {
-    let filename = select_date().format("%Y-%m-%d").to_string();
-    let path = files::get_document_file_path_from(&filename).expect("Cannot convert filename to path");
-    let mut entries: EntryList = repository::load_entry_list(&path).expect("Cannot load entries");
+pub fn add_entry(config: &Config, args: &AddArgs) -> TickResult<()> {
+    let date = match args.date {
+        Some(date) => date,
+        None => select_date(),
+    };
+    let filename = date.format("%Y-%m-%d").to_string();
+    let path = files::get_document_file_path_from(&filename)?;
+    let mut entries: EntryList = repository::load_entry_list(&path)?;
 
-    let project = select_project(config);
-    let mut task = None;
-    if project.is_some() {
-        task = select_task(config, &project.as_ref().unwrap().get_id());
+    let booking = choose_booking(
+        config,
+        args.kind,
+        args.template.as_ref(),
+        args.project.as_ref(),
+        args.task.as_ref(),
+    )?;
+    // Entries of just hours have no times to ask for.
+    let (start_time, end_time) = match args.hours {
+        Some(_) => (None, None),
+        None => {
+            let (start_time, end_time) = choose_times(args, &booking, &entries);
+            (Some(start_time), end_time)
+        }
+    };
+    let notes = match args.notes.as_ref().or(booking.get_template_notes()) {
+        Some(notes) => notes.clone(),
+        None if args.yes => String::new(),
+        None => input_notes(),
+    };
+
+    let Booking { kind, project_name, task_id, task_name, .. } = booking;
+
+    // Selecting no cancels without changing anything.
+    if !args.yes
+        && !confirm_entry(&project_name, &task_name, start_time.as_ref(), end_time.as_ref(), args.hours, &notes)
+    {
+        return Err(TickError::Cancelled);
     }
-    let start_time = input_start_time();
-    let end_time = input_end_time();
-    let notes = input_notes();
 
-    let project_name = match project {
-        Some(project) => Some(project.get_name().clone()),
-        None => None,
+    let mut entry = match (start_time, args.hours) {
+        (Some(start_time), _) => Entry::create(
+            project_name,
+            task_id,
+            task_name,
+            start_time,
+            end_time.map(|(end_time, _)| end_time),
+            notes,
+        ),
+        (None, hours) => Entry::create_with_hours(project_name, task_id, task_name, hours.unwrap(), notes),
     };
-    let (task_id, task_name) = match task {
-        Some(task) => (Some(*task.get_id()), Some(task.get_name().clone())),
-        None => (None, None)
+    entry.set_ends_next_day(end_time.is_some_and(|(_, next_day)| next_day));
+    entry.set_kind(kind);
+    entries.add(entry);
+
+    warn_about_problems(&entries);
+
+    repository::store_entry_list(&entries, &path)?;
+
+    Ok(())
+}
+
+/// Takes the start and end time from the arguments or template, or asks
+/// for them.
+fn choose_times(
+    args: &AddArgs,
+    booking: &Booking,
+    entries: &EntryList,
+) -> (NaiveTime, Option<(NaiveTime, bool)>) {
+    let previous_end_time = entries.get_end_time_before(entries.get_all().len());
+    let start_time = args.start.unwrap_or_else(|| input_start_time(previous_end_time));
+    let template_end_time = booking
+        .template
+        .as_ref()
+        .and_then(|t| t.get_end_time(&start_time))
+        // An end before the start means the template ran past midnight.
+        .map(|end_time| (end_time, end_time < start_time));
+    let end_time = match args.end.or(template_end_time) {
+        Some(end_time) => Some(end_time),
+        None if args.yes => None,
+        None => input_end_time(previous_end_time),
     };
 
-    // Selecting no means gracefully termination.
-    if confirm_entry(&project_name, &task_name, &start_time, end_time.as_ref(), &notes).is_none() {
-        return Ok(());
+    (start_time, end_time)
+}
+
+/// What an entry is booked on, possibly taken from a template.
+pub(super) struct Booking {
+    pub kind: EntryKind,
+    pub project_name: Option<String>,
+    pub task_id: Option<u32>,
+    pub task_name: Option<String>,
+    pub template: Option<Template>,
+}
+
+impl Booking {
+    fn from_template(template: Template) -> Self {
+        Self {
+            kind: EntryKind::Work,
+            project_name: template.get_project_name().cloned(),
+            task_id: template.get_task_id().copied(),
+            task_name: template.get_task_name().cloned(),
+            template: Some(template),
+        }
+    }
+
+    fn from_project(project: Option<Project>, task: Option<Task>) -> Self {
+        Self {
+            kind: EntryKind::Work,
+            project_name: project.map(|p| p.get_name().clone()),
+            task_id: task.as_ref().map(|t| *t.get_id()),
+            task_name: task.map(|t| t.get_name().clone()),
+            template: None,
+        }
     }
 
-    entries.add(Entry::create(
-        project_name,
-        task_id,
-        task_name,
-        start_time,
-        end_time,
-        notes,
-    ));
+    /// Breaks and personal time aren't booked on a project.
+    fn from_kind(kind: EntryKind) -> Self {
+        Self {
+            kind,
+            project_name: None,
+            task_id: None,
+            task_name: None,
+            template: None,
+        }
+    }
 
-    repository::store_entry_list(&entries, &path).expect("Cannot store entry list");
+    pub fn get_template_notes(&self) -> Option<&String> {
+        self.template
+            .as_ref()
+            .map(|t| t.get_notes())
+            .filter(|notes| !notes.is_empty())
+    }
+}
 
-    Ok(())
+enum Choice {
+    Template(Template),
+    Project(Project),
+    Kind(EntryKind),
+    Nothing,
 }
 
-fn select_project(config: &Config) -> Option<Project> {
-    let projects: Vec<Project> = match api::get_projects(config) {
-        Ok(projects) => projects,
-        Err(e) => {
-            println!("{}", e.message());
-            process::exit(1)
+/// Resolves the given kind, template, or project and task names, or asks
+/// for them when they aren't given. Templates are offered above the
+/// projects, and breaks and personal time below them.
+pub(super) fn choose_booking(
+    config: &Config,
+    kind: Option<EntryKind>,
+    template_name: Option<&String>,
+    project_name: Option<&String>,
+    task_name: Option<&String>,
+) -> TickResult<Booking> {
+    if let Some(kind) = kind.filter(|kind| *kind != EntryKind::Work) {
+        return Ok(Booking::from_kind(kind));
+    }
+
+    if let Some(name) = template_name {
+        return Ok(Booking::from_template(find_template(config, name)?));
+    }
+
+    if project_name.is_some() {
+        let (project, task) = choose_project_and_task(config, project_name, task_name)?;
+        return Ok(Booking::from_project(project, task));
+    }
+
+    let booking = match select_booking(config)? {
+        Choice::Template(template) => Booking::from_template(template),
+        Choice::Project(project) => {
+            let task = select_task(config, project.get_id())?;
+            Booking::from_project(Some(project), task)
         }
+        Choice::Kind(kind) => Booking::from_kind(kind),
+        Choice::Nothing => Booking::from_project(None, None),
     };
 
-    let project_names: Vec<String> = projects.iter().map(|p| p.get_name().clone()).collect();
+    Ok(booking)
+}
 
-    match ui::fuzzy_select("Select a project", &project_names, Some(0), true) {
-        Some(index) => Some(projects[index].clone()),
-        None => None,
+/// Resolves the given project and task names, or asks for them when
+/// they aren't given.
+pub(super) fn choose_project_and_task(
+    config: &Config,
+    project_name: Option<&String>,
+    task_name: Option<&String>,
+) -> TickResult<(Option<Project>, Option<Task>)> {
+    let project = match project_name {
+        Some(name) => Some(find_project(config, name)?),
+        None => select_project(config)?,
+    };
+    let mut task = None;
+    if let Some(project) = &project {
+        task = match task_name {
+            Some(name) => Some(find_task(config, project.get_id(), name)?),
+            None => select_task(config, project.get_id())?,
+        };
     }
+
+    Ok((project, task))
 }
 
-fn select_task(config: &Config, project_id: &u32) -> Option<Task> {
-    let tasks: Vec<Task> = match api::get_tasks(config, project_id) {
-        Ok(tasks) => tasks,
-        Err(e) => {
-            println!("{}", e.message());
-            process::exit(1)
-        }
+fn select_project(config: &Config) -> TickResult<Option<Project>> {
+    let projects = api::get_projects(config)?;
+
+    let project_names: Vec<String> = projects.iter().map(|p| p.get_name().clone()).collect();
+
+    Ok(ui::fuzzy_select("Select a project", &project_names, Some(0), true)
+        .map(|index| projects[index].clone()))
+}
+
+fn select_booking(config: &Config) -> TickResult<Choice> {
+    const KINDS: [EntryKind; 2] = [EntryKind::Break, EntryKind::Personal];
+
+    let templates = config.get_templates();
+    let projects = api::get_projects(config)?;
+
+    let mut names: Vec<String> = templates.iter().map(|t| format!("* {}", t)).collect();
+    names.extend(projects.iter().map(|p| p.get_name().clone()));
+    names.extend(KINDS.iter().map(|kind| format!("({})", kind)));
+
+    let index = match ui::fuzzy_select("Select a project", &names, Some(0), true) {
+        Some(index) => index,
+        None => return Ok(Choice::Nothing),
+    };
+
+    let choice = if index < templates.len() {
+        Choice::Template(templates[index].clone())
+    } else if index < templates.len() + projects.len() {
+        Choice::Project(projects[index - templates.len()].clone())
+    } else {
+        Choice::Kind(KINDS[index - templates.len() - projects.len()])
     };
 
+    Ok(choice)
+}
+
+fn select_task(config: &Config, project_id: &u32) -> TickResult<Option<Task>> {
+    let tasks = api::get_tasks(config, project_id)?;
+
     let task_names: Vec<String> = tasks.iter().map(|t| t.get_name().clone()).collect();
 
-    match ui::fuzzy_select("Select a task", &task_names, Some(0), true) {
-        Some(index) => Some(tasks[index].clone()),
-        None => None,
+    Ok(ui::fuzzy_select("Select a task", &task_names, Some(0), true)
+        .map(|index| tasks[index].clone()))
+}
+
+fn find_template(config: &Config, name: &str) -> TickResult<Template> {
+    match config.find_template(name) {
+        Some(template) => Ok(template.clone()),
+        None => Err(TickError::Other(format!(
+            "No template named \"{}\". Run {} to see them all.",
+            name,
+            style("tick template list").bold(),
+        ))),
     }
 }
 
+pub(super) fn find_project(config: &Config, name: &str) -> TickResult<Project> {
+    let projects = api::get_projects(config)?;
+    let project_names: Vec<String> = projects.iter().map(|p| p.get_name().clone()).collect();
+
+    match tick_cli::find_by_name(&project_names, name) {
+        Ok(index) => Ok(projects[index].clone()),
+        Err(e) => Err(match_error("project", name, e)),
+    }
+}
+
+fn find_task(config: &Config, project_id: &u32, name: &str) -> TickResult<Task> {
+    let tasks = api::get_tasks(config, project_id)?;
+    let task_names: Vec<String> = tasks.iter().map(|t| t.get_name().clone()).collect();
+
+    match tick_cli::find_by_name(&task_names, name) {
+        Ok(index) => Ok(tasks[index].clone()),
+        Err(e) => Err(match_error("task", name, e)),
+    }
+}
+
+fn match_error(kind: &str, name: &str, error: NameMatchError) -> TickError {
+    let message = match error {
+        NameMatchError::NotFound => format!("No {} found matching \"{}\"", kind, name),
+        NameMatchError::Ambiguous(names) => {
+            let mut message = format!("Multiple {}s match \"{}\":", kind, name);
+            for name in names {
+                message += &format!("\n  {}", name);
+            }
+            message
+        }
+    };
+
+    TickError::Other(message)
+}
+
 fn select_date() -> NaiveDate {
-    let initial_text = Utc::now().format("%Y-%m-%d").to_string();
-    
+    let initial_text = ui::today().format("%Y-%m-%d").to_string();
+
     ui::date("Select a date", Some(&initial_text)).unwrap()
 }
 
-fn input_start_time() -> NaiveTime {
-    ui::time("Input start time", None, false).unwrap()
+fn input_start_time(previous: Option<NaiveTime>) -> NaiveTime {
+    ui::time("Input start time", None, false, previous).unwrap()
 }
 
-fn input_end_time() -> Option<NaiveTime> {
-    ui::time("Input end time", None, true)
+fn input_end_time(previous: Option<NaiveTime>) -> Option<(NaiveTime, bool)> {
+    ui::end_time("Input end time", None, true, previous)
 }
 
-fn input_notes() -> String {
+pub(super) fn input_notes() -> String {
     ui::default("Input notes", None)
 }
 
 fn confirm_entry(
     project_name: &Option<String>,
     task_name: &Option<String>,
-    start_time: &NaiveTime,
-    end_time: Option<&NaiveTime>,
+    start_time: Option<&NaiveTime>,
+    end_time: Option<&(NaiveTime, bool)>,
+    hours: Option<f64>,
     notes: &String,
-) -> Option<bool> {
+) -> bool {
     println!("This will add an entry with the following data:");
 
     let empty_string = String::new();
     let project = match project_name.as_ref() { Some(p) => p, None => &empty_string };
     let task = match task_name.as_ref() { Some(t) => t, None => &empty_string };
     let formatted_end_time = match end_time.as_ref() { 
-        Some(e) => e.format("%H:%M").to_string(),
+        Some((e, next_day)) => ui::format_time(e, *next_day),
         None => empty_string.clone()
     };
 
     println!("  Project: {}",  project);
     println!("  Task: {}", task);
-    println!("  Start Time: {}", start_time.format("%H:%M"));
-    println!("  End Time: {}", &formatted_end_time);
+    if let Some(start_time) = start_time {
+        println!("  Start Time: {}", start_time.format("%H:%M"));
+        println!("  End Time: {}", &formatted_end_time);
+    }
+    if let Some(hours) = hours {
+        println!("  Hours: {:.2}", hours);
+    }
     println!("  Notes: {}", notes);
 
     ui::confirm("Continue?")
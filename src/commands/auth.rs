@@ -1,16 +1,47 @@
-use std::process;
 use dialoguer::{Input, theme::ColorfulTheme, Password, console::style};
 use tick_cli::Role;
-use crate::{api, config::Config};
+use crate::{api, args::LoginArgs, config::{Config, CredentialStore}, error::{TickError, TickResult}, ui};
 
-pub fn check_auth(config: &Config) {
-    if config.missing_api_key() {
-        println!("To get started with Tick CLI, please run {}", style("tick login").bold());
-        process::exit(1)
+pub fn check_auth(config: &Config) -> TickResult<()> {
+    if config.missing_api_key() || config.get_subscription_id()? == 0 {
+        return Err(TickError::not_logged_in());
+    }
+
+    Ok(())
+}
+
+pub fn login(config: &mut Config, args: &LoginArgs) -> TickResult<()> {
+    match (&args.token, args.subscription) {
+        (Some(token), Some(subscription_id)) => login_with_token(config, token, subscription_id),
+        _ => login_with_password(config),
     }
 }
 
-pub fn login(config: &mut Config) -> std::io::Result<()> {
+fn login_with_password(config: &mut Config) -> TickResult<()> {
+    let roles = request_roles(config)?;
+
+    let companies: Vec<&String> = roles.iter().map(|r| r.get_company()).collect();
+    let role = &roles[select_company(&companies, None)?];
+
+    let users = api::get_users_with_token(config, *role.get_subscription_id(), role.get_api_token())?;
+    let first_name = users
+        .first()
+        .ok_or(TickError::Auth("Tick didn't list any users for this subscription.".to_string()))?
+        .get_first_name();
+
+    config.use_role(role);
+    config.set_subscriptions(&roles);
+
+    config.store()?;
+
+    println!("Logged in as {} from {}", style(first_name).bold(), style(role.get_company()).bold());
+
+    store_api_key(config)
+}
+
+/// Asks for the email and password of the account, and gets the roles,
+/// with the token of each subscription, from Tick.
+fn request_roles(config: &Config) -> TickResult<Vec<Role>> {
     let email = Input::with_theme(&ColorfulTheme::default())
         .with_prompt("Email")
         .interact()
@@ -21,48 +52,124 @@ pub fn login(config: &mut Config) -> std::io::Result<()> {
         .interact()
         .unwrap();
 
-    let roles = match api::get_roles(&email, &password) {
+    let roles = match api::get_roles(config, &email, &password) {
         Ok(roles) => roles,
-        Err(e) if e.is_unauthenticated_error() => {
-            println!("Invalid credentials provided. Please try again.");
-            process::exit(1)
-        }
-        Err(e) => {
-            panic!("Unexpected error: {}", e.message());
+        Err(TickError::Auth(_)) => {
+            return Err(TickError::Auth("Invalid credentials provided. Please try again.".to_string()))
         }
+        Err(e) => return Err(e),
     };
 
-    let role: &Role = roles.first().unwrap();
-
-    config.set_subscription_id(role.get_subscription_id().clone());
-    config.set_api_key(role.get_api_token().clone());
+    if roles.is_empty() {
+        return Err(TickError::Auth("No Tick subscriptions found for this account.".to_string()));
+    }
 
-    config.store().expect("Failed logging in");
+    Ok(roles)
+}
 
-    let users = match api::get_users(config) {
+/// Logs in without a password, like for accounts that sign in through SSO.
+/// Tick tells nothing about the token other than the users it can see, so
+/// that's what checks it.
+fn login_with_token(config: &mut Config, token: &str, subscription_id: u32) -> TickResult<()> {
+    let users = match api::get_users_with_token(config, subscription_id, token) {
         Ok(users) => users,
-        Err(e) => {
-            println!("{}", e.message());
-            process::exit(1)
+        Err(TickError::Auth(_)) | Err(TickError::Validation(_)) => {
+            return Err(TickError::Auth(format!(
+                "Tick rejected this token for subscription {}. Check both on your Tick profile page.",
+                subscription_id,
+            )))
         }
+        Err(e) => return Err(e),
     };
-    let first_name = users.first().unwrap().get_first_name();
-    println!("Logged in as {} from {}", style(first_name).bold(), style(role.get_company()).bold());
+
+    config.use_token(subscription_id, token);
+    config.set_subscriptions(&[]);
+    config.store()?;
+
+    match users.first() {
+        Some(user) => println!("Logged in as {}", style(user.get_first_name()).bold()),
+        None => println!("Logged in to subscription {}", style(subscription_id).bold()),
+    }
+
+    store_api_key(config)
+}
+
+/// Hands the new token to the credential helper, or tells where it's
+/// expected when it's not up to tick to keep it.
+fn store_api_key(config: &Config) -> TickResult<()> {
+    match config.get_credential_store() {
+        CredentialStore::Command if !config.store_api_key()? => println!(
+            "No {} configured, so make sure {} prints the API token of this subscription",
+            style("api_key_store_command").bold(),
+            style("api_key_command").bold(),
+        ),
+        CredentialStore::Env => println!(
+            "Make sure {} holds the API token of this subscription",
+            style("TICK_API_TOKEN").bold(),
+        ),
+        _ => {}
+    }
 
     Ok(())
 }
 
-pub fn logout(config: &Config) -> std::io::Result<()> {
+pub fn logout(config: &Config) -> TickResult<()> {
     if config.missing_api_key() {
         println!("You are not logged in. Run {} to authenticate.", style("tick login").bold());
 
         return Ok(());
     }
 
-    match Config::reset() {
-        Ok(()) => println!("Logged out successfully"),
-        Err(e) => println!("Logging out failed: {}", e),
-    }
+    config.reset()?;
+    println!("Logged out successfully");
 
     Ok(())
 }
+
+pub fn switch_account(config: &mut Config) -> TickResult<()> {
+    let subscriptions = config.get_subscriptions().clone();
+
+    if subscriptions.is_empty() {
+        println!(
+            "No other subscriptions known. Run {} again to load them.",
+            style("tick login").bold(),
+        );
+        return Ok(());
+    }
+
+    let companies: Vec<&String> = subscriptions.iter().map(|s| s.get_company()).collect();
+    let current_id = config.get_subscription_id()?;
+    let current = subscriptions
+        .iter()
+        .position(|s| s.get_subscription_id() == current_id);
+    let subscription = &subscriptions[select_company(&companies, current)?];
+
+    // Tokens aren't kept for the other subscriptions, so get them again.
+    let roles = request_roles(config)?;
+    let role = roles
+        .iter()
+        .find(|r| *r.get_subscription_id() == subscription.get_subscription_id())
+        .ok_or(TickError::Auth(format!(
+            "This account has no access to {} anymore.",
+            subscription.get_company(),
+        )))?;
+
+    config.use_role(role);
+    config.set_subscriptions(&roles);
+    config.store()?;
+
+    println!("Switched to {}", style(role.get_company()).bold());
+
+    store_api_key(config)
+}
+
+fn select_company(companies: &[&String], current: Option<usize>) -> TickResult<usize> {
+    if companies.len() == 1 {
+        return Ok(0);
+    }
+
+    match ui::fuzzy_select("Select a subscription", companies, current.or(Some(0)), false) {
+        Some(index) => Ok(index),
+        None => Err(TickError::Cancelled),
+    }
+}
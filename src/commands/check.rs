@@ -0,0 +1,41 @@
+use dialoguer::console::style;
+use tick_cli::EntryList;
+use crate::{args::CheckArgs, error::{TickError, TickResult}, files, repository, ui};
+
+pub fn check_day(args: &CheckArgs) -> TickResult<()> {
+    let date = args.date.unwrap_or_else(ui::today);
+    let filename = date.format("%Y-%m-%d").to_string();
+    let path = files::get_document_file_path_from(&filename)?;
+    let entries = repository::load_entry_list(&path)?;
+
+    if entries.is_empty() {
+        println!("No entries on {}", filename);
+        return Ok(());
+    }
+
+    print!("{}", entries);
+
+    let problems = entries.validate();
+    if problems.is_empty() {
+        println!("No problems found");
+        return Ok(());
+    }
+
+    for problem in &problems {
+        println!("{}", problem);
+    }
+
+    if problems.iter().any(|p| p.is_blocking()) {
+        return Err(TickError::Validation(format!("{} can't be submitted as it is", filename)));
+    }
+
+    Ok(())
+}
+
+/// Prints the problems of a day as warnings, to point them out while
+/// entries are still being added or edited.
+pub(super) fn warn_about_problems(entries: &EntryList) {
+    for problem in entries.validate() {
+        println!("{} {}", style("Warning:").yellow().bold(), problem);
+    }
+}
@@ -1,18 +1,18 @@
 use tick_cli::{EntryList, Entry};
-use crate::{config::Config, api, repository, ui};
+use crate::{config::Config, api, error::{TickError, TickResult}, repository, ui};
 
-pub fn delete_entry(config: &Config) -> std::io::Result<()> {
-    let path = ui::select_file();
-    let entries = repository::load_entry_list(&path).expect("Cannot load entries");
+pub fn delete_entry(config: &Config) -> TickResult<()> {
+    let path = ui::select_file()?;
+    let entries = repository::load_entry_list(&path)?;
 
-    let (index, entry) = select_entry(&entries).unwrap();
+    let (index, entry) = select_entry(&entries)?;
 
-    if confirm_deletion().is_none() {
-        return Ok(())
+    if !confirm_deletion() {
+        return Err(TickError::Cancelled);
     }
 
     if let Some(id) = entry.get_tick_id() {
-        api::delete_entry(config, *id).expect("Unable to delete entry from tickspot");
+        api::delete_entry(config, *id)?;
     }
 
     let mut updated_entries = entries.clone();
@@ -20,9 +20,9 @@ pub fn delete_entry(config: &Config) -> std::io::Result<()> {
     updated_entries.remove(index);
 
     if updated_entries.is_empty() {
-        repository::delete_entry_list(&path).expect("Unable to delete file");
+        repository::delete_entry_list(&path)?;
     } else {
-        repository::store_entry_list(&updated_entries, &path).expect("Unable to store entry list");
+        repository::store_entry_list(&updated_entries, &path)?;
     }
 
     println!("Entry succesfully removed");
@@ -30,15 +30,16 @@ pub fn delete_entry(config: &Config) -> std::io::Result<()> {
     Ok(())
 }
 
-fn select_entry(entry_list: &EntryList) -> Option<(usize, &Entry)> {
+#[allow(clippy::clone_on_copy)]
+fn select_entry(entry_list: &EntryList) -> TickResult<(usize, &Entry)> {
     let entries = entry_list.get_all();
 
     match ui::fuzzy_select("Select an entry", entries, Some(0), false) {
-        Some(index) => Some((index.clone(), entry_list.get(index))),
-        None => panic!("Nothing selected"),
+        Some(index) => Ok((index.clone(), entry_list.get(index))),
+        None => Err(TickError::Cancelled),
     }
 }
 
-fn confirm_deletion() -> Option<bool> {
+fn confirm_deletion() -> bool {
     ui::confirm("Are you sure you want to delete this entry?")
 }
@@ -1,25 +1,35 @@
-use std::process;
 use chrono::NaiveTime;
-use tick_cli::{Entry, EntryList, Project, Task};
-use crate::{api, config::Config, repository, ui};
+use tick_cli::{EntryList, Project, Task};
+use crate::{api, config::Config, error::{TickError, TickResult}, repository, ui};
+use super::check::warn_about_problems;
 
-pub fn edit_entry(config: &Config) -> std::io::Result<()> {
-    let path = ui::select_file();
-    let mut entries: EntryList = repository::load_entry_list(&path).expect("Cannot load entries");
+#[allow(clippy::unnecessary_unwrap, clippy::needless_borrow, clippy::manual_map)]
+pub fn edit_entry(config: &Config) -> TickResult<()> {
+    let path = ui::select_file()?;
+    let mut entries: EntryList = repository::load_entry_list(&path)?;
 
-    let entry = select_entry(&mut entries).unwrap();
+    let index = select_entry(&entries)?;
+    let previous_end_time = entries.get_end_time_before(index);
+    let entry = entries.get_mut(index);
 
-    let project = select_project(config, entry.get_project_name());
+    let project = select_project(config, entry.get_project_name())?;
     let mut task = None;
     if project.is_some() {
         task = select_task(
             config,
             &project.as_ref().unwrap().get_id(),
             entry.get_task_name(),
-        );
+        )?;
     }
-    let start_time = input_start_time(entry.get_start_time());
-    let end_time = input_end_time(entry.get_end_time());
+    // Entries of just hours keep being just hours.
+    let (start_time, end_time, hours) = match entry.get_start_time() {
+        Some(start_time) => (
+            Some(input_start_time(start_time, previous_end_time)),
+            input_end_time(entry.get_end_time(), entry.ends_next_day(), previous_end_time),
+            None,
+        ),
+        None => (None, None, Some(input_hours(entry.calculate_hours()))),
+    };
     let notes = input_notes(entry.get_notes());
 
     let project_name = match project {
@@ -31,45 +41,47 @@ pub fn edit_entry(config: &Config) -> std::io::Result<()> {
         None => (None, None),
     };
 
-    // Selecting no means gracefully termination.
-    if confirm_entry(&project_name, &task_name, &start_time, end_time.as_ref(), &notes).is_none() {
-        return Ok(());
+    // Selecting no cancels without changing anything.
+    if !confirm_entry(&project_name, &task_name, start_time.as_ref(), end_time.as_ref(), hours, &notes) {
+        return Err(TickError::Cancelled);
     }
 
-    entry.update(
-        project_name,
-        task_id,
-        task_name,
-        start_time,
-        end_time,
-        notes,
-    );
+    match (start_time, hours) {
+        (Some(start_time), _) => {
+            entry.update(
+                project_name,
+                task_id,
+                task_name,
+                start_time,
+                end_time.map(|(end_time, _)| end_time),
+                notes,
+            );
+            entry.set_ends_next_day(end_time.is_some_and(|(_, next_day)| next_day));
+        }
+        (None, hours) => entry.update_with_hours(project_name, task_id, task_name, hours.unwrap(), notes),
+    }
 
     entries.sort();
     entries.set_all_submitted(false);
 
-    repository::store_entry_list(&entries, &path).expect("Cannot store entry list");
+    warn_about_problems(&entries);
+
+    repository::store_entry_list(&entries, &path)?;
 
     Ok(())
 }
 
-fn select_entry(entry_list: &mut EntryList) -> Option<&mut Entry> {
+fn select_entry(entry_list: &EntryList) -> TickResult<usize> {
     let entries = entry_list.get_all();
 
     match ui::fuzzy_select("Select an entry", entries, Some(0), false) {
-        Some(index) => Some(entry_list.get_mut(index)),
-        None => panic!("Nothing selected"),
+        Some(index) => Ok(index),
+        None => Err(TickError::Cancelled),
     }
 }
 
-fn select_project(config: &Config, selected: Option<&String>) -> Option<Project> {
-    let projects: Vec<Project> = match api::get_projects(config) {
-        Ok(projects) => projects,
-        Err(e) => {
-            println!("{}", e.message());
-            process::exit(1)
-        }
-    };
+fn select_project(config: &Config, selected: Option<&String>) -> TickResult<Option<Project>> {
+    let projects: Vec<Project> = api::get_projects(config)?;
 
     let project_names: Vec<String> = projects.iter().map(|p| p.get_name().clone()).collect();
     let mut selected_index = None;
@@ -78,19 +90,13 @@ fn select_project(config: &Config, selected: Option<&String>) -> Option<Project>
     }
 
     match ui::fuzzy_select("Select a project", &project_names, selected_index, true) {
-        Some(index) => Some(projects[index].clone()),
-        None => None,
+        Some(index) => Ok(Some(projects[index].clone())),
+        None => Ok(None),
     }
 }
 
-fn select_task(config: &Config, project_id: &u32, selected: Option<&String>) -> Option<Task> {
-    let tasks: Vec<Task> = match api::get_tasks(config, project_id) {
-        Ok(tasks) => tasks,
-        Err(e) => {
-            println!("{}", e.message());
-            process::exit(1)
-        }
-    };
+fn select_task(config: &Config, project_id: &u32, selected: Option<&String>) -> TickResult<Option<Task>> {
+    let tasks: Vec<Task> = api::get_tasks(config, project_id)?;
 
     let task_names: Vec<String> = tasks.iter().map(|t| t.get_name().clone()).collect();
     let mut selected_index = None;
@@ -99,29 +105,31 @@ fn select_task(config: &Config, project_id: &u32, selected: Option<&String>) ->
     }
 
     match ui::fuzzy_select("Select a task", &task_names, selected_index, true) {
-        Some(index) => Some(tasks[index].clone()),
-        None => None,
+        Some(index) => Ok(Some(tasks[index].clone())),
+        None => Ok(None),
     }
 }
 
-fn input_start_time(start_time: &NaiveTime) -> NaiveTime {
+fn input_start_time(start_time: &NaiveTime, previous: Option<NaiveTime>) -> NaiveTime {
     let initial = start_time.format("%H:%M").to_string();
 
-    ui::time("Input start time", Some(&initial), false).unwrap()
+    ui::time("Input start time", Some(&initial), false, previous).unwrap()
 }
 
-fn input_end_time(end_time: Option<&NaiveTime>) -> Option<NaiveTime> {
-    let mut formatted = String::new();
-    if end_time.is_some() {
-        formatted = end_time.unwrap().format("%H:%M").to_string();
-    }
+fn input_end_time(
+    end_time: Option<&NaiveTime>,
+    next_day: bool,
+    previous: Option<NaiveTime>,
+) -> Option<(NaiveTime, bool)> {
+    let initial = end_time.map(|end_time| ui::format_time(end_time, next_day));
 
-    let mut initial = None;
-    if formatted.is_empty() {
-        initial = Some(&formatted);
-    }
+    ui::end_time("Input end time", initial.as_ref(), true, previous)
+}
 
-    ui::time("Input end time", initial, true)
+fn input_hours(hours: f64) -> f64 {
+    let initial = format!("{}", hours);
+
+    ui::hours("Input hours", Some(&initial))
 }
 
 fn input_notes(notes: &String) -> String {
@@ -131,24 +139,30 @@ fn input_notes(notes: &String) -> String {
 fn confirm_entry(
     project_name: &Option<String>,
     task_name: &Option<String>,
-    start_time: &NaiveTime,
-    end_time: Option<&NaiveTime>,
+    start_time: Option<&NaiveTime>,
+    end_time: Option<&(NaiveTime, bool)>,
+    hours: Option<f64>,
     notes: &String,
-) -> Option<bool> {
+) -> bool {
     println!("This will update the entry with the following data:");
 
     let empty_string = String::new();
     let project = match project_name.as_ref() { Some(p) => p, None => &empty_string };
     let task = match task_name.as_ref() { Some(t) => t, None => &empty_string };
     let formatted_end_time = match end_time.as_ref() { 
-        Some(e) => e.format("%H:%M").to_string(),
+        Some((e, next_day)) => ui::format_time(e, *next_day),
         None => empty_string.clone()
     };
 
     println!("  Project: {}", project);
     println!("  Task: {}", task);
-    println!("  Start Time: {}", start_time.format("%H:%M"));
-    println!("  End Time: {}", &formatted_end_time);
+    if let Some(start_time) = start_time {
+        println!("  Start Time: {}", start_time.format("%H:%M"));
+        println!("  End Time: {}", &formatted_end_time);
+    }
+    if let Some(hours) = hours {
+        println!("  Hours: {:.2}", hours);
+    }
     println!("  Notes: {}", notes);
 
     ui::confirm("Continue?")
@@ -0,0 +1,181 @@
+use std::fs;
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use serde::Serialize;
+use tick_cli::Entry;
+use crate::{args::{ExportArgs, ExportFormat}, error::{TickError, TickResult}, repository, ui};
+
+const CSV_HEADER: [&str; 9] = [
+    "date", "start", "end", "hours", "project", "task", "notes", "tick_id", "state",
+];
+
+#[derive(Serialize)]
+struct Row {
+    #[serde(skip)]
+    day: NaiveDate,
+    #[serde(skip)]
+    start_time: Option<NaiveTime>,
+    #[serde(skip)]
+    ends_at: Option<NaiveDateTime>,
+    date: String,
+    start: Option<String>,
+    end: Option<String>,
+    hours: f64,
+    project: Option<String>,
+    task: Option<String>,
+    notes: String,
+    tick_id: Option<u32>,
+    state: &'static str,
+}
+
+impl Row {
+    fn from_entry(day: NaiveDate, entry: &Entry) -> Self {
+        Self {
+            day,
+            start_time: entry.get_start_time().copied(),
+            ends_at: entry.get_end_time().map(|end_time| {
+                let end_day = if entry.ends_next_day() { day + Duration::days(1) } else { day };
+                end_day.and_time(*end_time)
+            }),
+            date: day.format("%Y-%m-%d").to_string(),
+            start: entry.get_start_time().map(|s| s.format("%H:%M").to_string()),
+            end: entry.get_end_time().map(|e| ui::format_time(e, entry.ends_next_day())),
+            hours: entry.calculate_hours(),
+            project: entry.get_project_name().cloned(),
+            task: entry.get_task_name().cloned(),
+            notes: entry.get_notes().clone(),
+            tick_id: entry.get_tick_id().copied(),
+            state: if !entry.is_submitted() {
+                "pending"
+            } else if entry.should_be_updated() {
+                "updated"
+            } else {
+                "submitted"
+            },
+        }
+    }
+}
+
+pub fn export(args: &ExportArgs) -> TickResult<()> {
+    let (from, to) = args.range.range();
+
+    let mut rows = Vec::new();
+    for (date, _, entries) in repository::load_entry_lists(&from, &to)? {
+        let day = NaiveDate::parse_from_str(&date, "%Y-%m-%d").unwrap();
+
+        rows.extend(entries.get_all().iter().map(|entry| Row::from_entry(day, entry)));
+    }
+
+    let content = match args.format {
+        ExportFormat::Csv => to_csv(&rows),
+        ExportFormat::Json => serde_json::to_string_pretty(&rows).expect("Cannot serialize entries") + "\n",
+        ExportFormat::Ics => to_ics(&rows),
+    };
+
+    match &args.output {
+        Some(path) => {
+            fs::write(path, content)
+                .map_err(|e| TickError::Storage(format!("Cannot write to {}: {}", path.display(), e)))?;
+            println!("Exported {} entries to {}", rows.len(), path.display());
+        }
+        None => print!("{}", content),
+    }
+
+    Ok(())
+}
+
+fn to_csv(rows: &[Row]) -> String {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(CSV_HEADER).expect("Cannot serialize entries");
+
+    for row in rows {
+        writer
+            .write_record([
+                row.date.clone(),
+                row.start.clone().unwrap_or_default(),
+                row.end.clone().unwrap_or_default(),
+                format!("{:.2}", row.hours),
+                row.project.clone().unwrap_or_default(),
+                row.task.clone().unwrap_or_default(),
+                row.notes.clone(),
+                row.tick_id.map(|id| id.to_string()).unwrap_or_default(),
+                row.state.to_string(),
+            ])
+            .expect("Cannot serialize entries");
+    }
+
+    String::from_utf8(writer.into_inner().expect("Cannot serialize entries")).unwrap()
+}
+
+fn to_ics(rows: &[Row]) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//tick-cli//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    for (index, row) in rows.iter().enumerate() {
+        let summary = match (&row.project, &row.task) {
+            (Some(project), Some(task)) => format!("{} | {}", project, task),
+            (Some(project), None) => project.clone(),
+            _ => "Tick entry".to_string(),
+        };
+        let uid = match (row.tick_id, row.start_time) {
+            (Some(id), _) => format!("tick-{}@tick-cli", id),
+            (None, Some(start_time)) => format!("{}-{}@tick-cli", row.day.format("%Y%m%d"), start_time.format("%H%M")),
+            (None, None) => format!("{}-{}@tick-cli", row.day.format("%Y%m%d"), index),
+        };
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", uid));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        // Times are stored without a timezone, so they're exported as
+        // floating local times.
+        match row.start_time {
+            Some(start_time) => lines.push(format!("DTSTART:{}", row.day.and_time(start_time).format("%Y%m%dT%H%M%S"))),
+            // Entries of just hours become all-day events.
+            None => lines.push(format!("DTSTART;VALUE=DATE:{}", row.day.format("%Y%m%d"))),
+        }
+        if let Some(ends_at) = row.ends_at {
+            lines.push(format!("DTEND:{}", ends_at.format("%Y%m%dT%H%M%S")));
+        }
+        lines.push(format!("SUMMARY:{}", escape_ics(&summary)));
+        if !row.notes.is_empty() {
+            lines.push(format!("DESCRIPTION:{}", escape_ics(&row.notes)));
+        }
+        lines.push(format!("STATUS:{}", if row.state == "pending" { "TENTATIVE" } else { "CONFIRMED" }));
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold_ics(line) + "\r\n").collect()
+}
+
+fn escape_ics(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Splits lines longer than 75 bytes, continuing them on lines that start
+/// with a space, as iCalendar requires.
+fn fold_ics(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded += "\r\n ";
+            length = 1;
+        }
+
+        folded.push(c);
+        length += c.len_utf8();
+    }
+
+    folded
+}
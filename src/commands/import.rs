@@ -0,0 +1,411 @@
+use std::collections::{BTreeMap, HashMap};
+use chrono::{Duration, NaiveDate, NaiveTime};
+use csv::StringRecord;
+use tick_cli::{Entry, Project, Task};
+use crate::{api, args::{ImportArgs, ImportFormat, IMPORT_FIELDS}, config::Config, files, repository, ui};
+use crate::error::{TickError, TickResult};
+
+const DATE_FORMATS: [&str; 3] = ["%Y-%m-%d", "%d-%m-%Y", "%d.%m.%Y"];
+/// Some exports put the month first and others the day.
+const SLASHED_DATE_FORMATS: [&str; 2] = ["%m/%d/%Y", "%d/%m/%Y"];
+const TIME_FORMATS: [&str; 4] = ["%H:%M:%S", "%H:%M", "%I:%M:%S %p", "%I:%M %p"];
+
+struct Record {
+    date: NaiveDate,
+    time: RecordTime,
+    project: Option<String>,
+    task: Option<String>,
+    notes: String,
+}
+
+/// Rows have a start and maybe an end time, or else just the hours worked.
+enum RecordTime {
+    Range(NaiveTime, Option<NaiveTime>),
+    Hours(f64),
+}
+
+pub fn import_entries(config: &Config, args: &ImportArgs) -> TickResult<()> {
+    let records = read_records(args)?;
+
+    let mut resolver = NameResolver::new(config, !args.dry_run && !args.yes);
+    let mut days: BTreeMap<NaiveDate, Vec<Entry>> = BTreeMap::new();
+
+    for record in records {
+        let (project, task) = resolver.resolve(record.project.as_ref(), record.task.as_ref())?;
+
+        let project_name = project.map(|p| p.get_name().clone());
+        let task_id = task.as_ref().map(|t| *t.get_id());
+        let task_name = task.map(|t| t.get_name().clone());
+
+        let entry = match record.time {
+            RecordTime::Range(start_time, end_time) => {
+                let mut entry = Entry::create(project_name, task_id, task_name, start_time, end_time, record.notes);
+                // Files only have end times, so an earlier one than the start
+                // means the entry crossed midnight.
+                entry.set_ends_next_day(end_time.is_some_and(|end_time| end_time < start_time));
+                entry
+            }
+            RecordTime::Hours(hours) => Entry::create_with_hours(project_name, task_id, task_name, hours, record.notes),
+        };
+
+        days.entry(record.date).or_default().push(entry);
+    }
+
+    let count: usize = days.values().map(|entries| entries.len()).sum();
+    for (date, entries) in &days {
+        println!("{}", date.format("%Y-%m-%d"));
+        for entry in entries {
+            println!("{}", entry);
+        }
+    }
+
+    if args.dry_run {
+        println!("Would import {} entries into {} days", count, days.len());
+        return Ok(());
+    }
+
+    if !args.yes && !confirm_import(count) {
+        return Err(TickError::Cancelled);
+    }
+
+    let mut imported_count = 0;
+    for (date, new_entries) in days {
+        let filename = date.format("%Y-%m-%d").to_string();
+        let path = files::get_document_file_path_from(&filename)?;
+        let mut entries = repository::load_entry_list(&path)?;
+
+        for entry in new_entries {
+            // Importing the same file twice shouldn't duplicate its entries.
+            if !entries.get_all().iter().any(|e| e.is_same_work(&entry)) {
+                entries.add(entry);
+                imported_count += 1;
+            }
+        }
+
+        repository::store_entry_list(&entries, &path)?;
+    }
+
+    println!("Imported {} entries", imported_count);
+    if imported_count < count {
+        println!("Skipped {} entries that were already imported", count - imported_count);
+    }
+
+    Ok(())
+}
+
+fn read_records(args: &ImportArgs) -> TickResult<Vec<Record>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(&args.file)
+        .map_err(|e| TickError::Storage(format!("Cannot read {}: {}", args.file.display(), e)))?;
+
+    let headers = reader
+        .headers()
+        .map_err(|e| TickError::Parse(format!("Cannot read the header row: {}", e)))?
+        .clone();
+    let columns = Columns::new(args, &headers)?;
+
+    reader
+        .records()
+        .enumerate()
+        .map(|(index, record)| {
+            // The header is the first line.
+            let line = index + 2;
+            let record = record.map_err(|e| TickError::Parse(format!("Cannot read line {}: {}", line, e)))?;
+
+            columns
+                .parse(&record)
+                .map_err(|message| TickError::Parse(format!("Line {}: {}", line, message)))
+        })
+        .collect()
+}
+
+/// Which column of the file each field is read from.
+struct Columns {
+    indexes: HashMap<&'static str, usize>,
+    date_format: Option<String>,
+}
+
+impl Columns {
+    fn new(args: &ImportArgs, headers: &StringRecord) -> TickResult<Self> {
+        let mut names: HashMap<&str, String> = default_columns(args.format)
+            .iter()
+            .map(|(field, column)| (*field, column.to_string()))
+            .collect();
+
+        for (field, column) in &args.columns {
+            let field = IMPORT_FIELDS.iter().find(|f| *f == field).unwrap();
+            names.insert(field, column.clone());
+        }
+
+        let indexes: HashMap<&'static str, usize> = IMPORT_FIELDS
+            .iter()
+            .filter_map(|field| {
+                let name = names.get(field)?;
+                let index = headers
+                    .iter()
+                    .position(|header| header.trim_start_matches('\u{feff}').trim().eq_ignore_ascii_case(name))?;
+
+                Some((*field, index))
+            })
+            .collect();
+
+        // Entries need a start time, or else just hours.
+        let required = if indexes.contains_key("duration") { "date" } else { "start" };
+        for field in ["date", required] {
+            if !indexes.contains_key(field) {
+                return Err(TickError::Parse(format!(
+                    "Column for {} not found. Use --column {}=<column> with one of: {}",
+                    field,
+                    field,
+                    headers.iter().collect::<Vec<&str>>().join(", "),
+                )));
+            }
+        }
+
+        Ok(Self { indexes, date_format: args.date_format.clone() })
+    }
+
+    fn get<'a>(&self, record: &'a StringRecord, field: &str) -> Option<&'a str> {
+        self.indexes
+            .get(field)
+            .and_then(|index| record.get(*index))
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    }
+
+    fn parse(&self, record: &StringRecord) -> Result<Record, String> {
+        let date = self.get(record, "date").ok_or("Missing date")?;
+        let date = parse_date(date, self.date_format.as_deref())?;
+
+        let duration = match self.get(record, "duration") {
+            Some(duration) => Some(parse_duration(duration).ok_or(format!("Unknown duration format: {}", duration))?),
+            None => None,
+        };
+
+        let time = match (self.get(record, "start"), duration) {
+            (Some(start), _) => {
+                let start_time = parse_with(start, &TIME_FORMATS, NaiveTime::parse_from_str)
+                    .ok_or(format!("Unknown time format: {}", start))?;
+
+                let end_time = match (self.get(record, "end"), duration) {
+                    (Some(end), _) => Some(
+                        parse_with(end, &TIME_FORMATS, NaiveTime::parse_from_str)
+                            .ok_or(format!("Unknown time format: {}", end))?,
+                    ),
+                    (None, Some(duration)) => Some(start_time + duration),
+                    (None, None) => None,
+                };
+
+                RecordTime::Range(start_time, end_time)
+            }
+            (None, Some(duration)) => {
+                let hours = duration.num_seconds() as f64 / 3600.0;
+                if hours <= 0.0 || hours > 24.0 {
+                    return Err(format!("Hours should be more than 0 and at most 24: {}", hours));
+                }
+
+                RecordTime::Hours(hours)
+            }
+            (None, None) => return Err("Missing start time or duration".to_string()),
+        };
+
+        Ok(Record {
+            date,
+            time,
+            project: self.get(record, "project").map(String::from),
+            task: self.get(record, "task").map(String::from),
+            notes: self.get(record, "notes").unwrap_or_default().to_string(),
+        })
+    }
+}
+
+fn default_columns(format: ImportFormat) -> [(&'static str, &'static str); 7] {
+    match format {
+        ImportFormat::TogglCsv => [
+            ("date", "Start date"),
+            ("start", "Start time"),
+            ("end", "End time"),
+            ("duration", "Duration"),
+            ("project", "Project"),
+            ("task", "Task"),
+            ("notes", "Description"),
+        ],
+        ImportFormat::ClockifyCsv => [
+            ("date", "Start Date"),
+            ("start", "Start Time"),
+            ("end", "End Time"),
+            ("duration", "Duration (h)"),
+            ("project", "Project"),
+            ("task", "Task"),
+            ("notes", "Description"),
+        ],
+        // The columns written by `tick export --format csv`.
+        ImportFormat::Csv => [
+            ("date", "date"),
+            ("start", "start"),
+            ("end", "end"),
+            ("duration", "hours"),
+            ("project", "project"),
+            ("task", "task"),
+            ("notes", "notes"),
+        ],
+    }
+}
+
+/// Parses a date in the given format, or else in any known one that reads it
+/// only one way.
+fn parse_date(input: &str, format: Option<&str>) -> Result<NaiveDate, String> {
+    if let Some(format) = format {
+        return NaiveDate::parse_from_str(input, format)
+            .map_err(|_| format!("Date {} doesn't match {}", input, format));
+    }
+
+    if let Some(date) = parse_with(input, &DATE_FORMATS, NaiveDate::parse_from_str) {
+        return Ok(date);
+    }
+
+    let mut dates: Vec<NaiveDate> = SLASHED_DATE_FORMATS
+        .iter()
+        .filter_map(|format| NaiveDate::parse_from_str(input, format).ok())
+        .collect();
+    dates.dedup();
+
+    match dates[..] {
+        [date] => Ok(date),
+        [] => Err(format!("Unknown date format: {}", input)),
+        _ => Err(format!(
+            "Date {} could be day or month first. Use --date-format %d/%m/%Y or %m/%d/%Y",
+            input,
+        )),
+    }
+}
+
+fn parse_with<T>(
+    input: &str,
+    formats: &[&str],
+    parse: fn(&str, &str) -> chrono::ParseResult<T>,
+) -> Option<T> {
+    formats.iter().find_map(|format| parse(input, format).ok())
+}
+
+/// Parses durations like 1:30, 01:30:00 or 1.5 (hours), of up to a day.
+fn parse_duration(input: &str) -> Option<Duration> {
+    if let Ok(hours) = input.parse::<f64>() {
+        return (0.0..=24.0)
+            .contains(&hours)
+            .then(|| Duration::minutes((hours * 60.0).round() as i64));
+    }
+
+    let parts: Vec<u32> = input.split(':').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    let (hours, minutes, seconds) = match parts[..] {
+        [hours, minutes] => (hours, minutes, 0),
+        [hours, minutes, seconds] => (hours, minutes, seconds),
+        _ => return None,
+    };
+
+    (hours <= 24).then(|| {
+        Duration::hours(hours.into()) + Duration::minutes(minutes.into()) + Duration::seconds(seconds.into())
+    })
+}
+
+/// Maps project and task names from the file to the ones in Tick. Names
+/// that can't be matched are asked for once, when interactive.
+struct NameResolver<'a> {
+    config: &'a Config,
+    interactive: bool,
+    projects: Option<Vec<Project>>,
+    tasks: HashMap<u32, Vec<Task>>,
+    resolved_projects: HashMap<String, Option<Project>>,
+    resolved_tasks: HashMap<(u32, String), Option<Task>>,
+}
+
+impl<'a> NameResolver<'a> {
+    fn new(config: &'a Config, interactive: bool) -> Self {
+        Self {
+            config,
+            interactive,
+            projects: None,
+            tasks: HashMap::new(),
+            resolved_projects: HashMap::new(),
+            resolved_tasks: HashMap::new(),
+        }
+    }
+
+    fn resolve(
+        &mut self,
+        project: Option<&String>,
+        task: Option<&String>,
+    ) -> TickResult<(Option<Project>, Option<Task>)> {
+        let project = match project {
+            Some(name) => self.resolve_project(name)?,
+            None => None,
+        };
+
+        let task = match (&project, task) {
+            (Some(project), Some(name)) => self.resolve_task(project, name)?,
+            _ => None,
+        };
+
+        Ok((project, task))
+    }
+
+    fn resolve_project(&mut self, name: &String) -> TickResult<Option<Project>> {
+        if let Some(project) = self.resolved_projects.get(name) {
+            return Ok(project.clone());
+        }
+
+        if self.projects.is_none() {
+            self.projects = Some(api::get_projects(self.config)?);
+        }
+        let projects = self.projects.as_ref().unwrap();
+        let project = resolve(projects, name, "project", self.interactive, |p| p.get_name());
+
+        self.resolved_projects.insert(name.clone(), project.clone());
+
+        Ok(project)
+    }
+
+    fn resolve_task(&mut self, project: &Project, name: &str) -> TickResult<Option<Task>> {
+        let key = (*project.get_id(), name.to_string());
+        if let Some(task) = self.resolved_tasks.get(&key) {
+            return Ok(task.clone());
+        }
+
+        if !self.tasks.contains_key(project.get_id()) {
+            self.tasks.insert(*project.get_id(), api::get_tasks(self.config, project.get_id())?);
+        }
+        let tasks = &self.tasks[project.get_id()];
+        let task = resolve(tasks, name, "task", self.interactive, |t| t.get_name());
+
+        self.resolved_tasks.insert(key, task.clone());
+
+        Ok(task)
+    }
+}
+
+fn resolve<T: Clone>(
+    items: &[T],
+    name: &str,
+    kind: &str,
+    interactive: bool,
+    get_name: fn(&T) -> &String,
+) -> Option<T> {
+    let names: Vec<String> = items.iter().map(|item| get_name(item).clone()).collect();
+
+    if let Ok(index) = tick_cli::find_by_name(&names, name) {
+        return Some(items[index].clone());
+    }
+
+    if !interactive {
+        println!("No {} found for \"{}\", importing without one", kind, name);
+        return None;
+    }
+
+    let prompt = format!("Select the {} for \"{}\"", kind, name);
+    ui::fuzzy_select(&prompt, &names, Some(0), true).map(|index| items[index].clone())
+}
+
+fn confirm_import(count: usize) -> bool {
+    ui::confirm(&format!("Import these {} entries?", count))
+}
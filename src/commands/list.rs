@@ -1,8 +1,8 @@
-use crate::{repository, ui};
+use crate::{error::TickResult, repository, ui};
 
-pub fn list_entries() -> std::io::Result<()> {
-    let path = ui::select_file();
-    let entries = repository::load_entry_list(&path).expect("Cannot load entries");
+pub fn list_entries() -> TickResult<()> {
+    let path = ui::select_file()?;
+    let entries = repository::load_entry_list(&path)?;
 
     print!("{}", entries);
 
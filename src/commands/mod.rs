@@ -4,6 +4,15 @@ mod list;
 mod edit;
 mod submit;
 mod delete;
+mod check;
+mod timer;
+mod profile;
+mod pull;
+mod report;
+mod export;
+mod import;
+mod template;
+mod rounding;
 
 pub use {
     add::*,
@@ -11,5 +20,14 @@ pub use {
     list::*,
     edit::*,
     submit::*,
-    delete::*
+    delete::*,
+    check::*,
+    timer::*,
+    profile::*,
+    pull::*,
+    report::*,
+    export::*,
+    import::*,
+    template::*,
+    rounding::*
 };
@@ -0,0 +1,114 @@
+use dialoguer::console::style;
+use crate::config::{self, Profiles, DEFAULT_PROFILE};
+use crate::error::{TickError, TickResult};
+use crate::ui;
+
+pub fn list_profiles(current: Option<&str>) -> TickResult<()> {
+    let profiles = config::load_profiles()?;
+    let current = current.unwrap_or(DEFAULT_PROFILE);
+
+    for name in profiles.get_names() {
+        let config = config::load(profile_option(name))?;
+
+        println!(
+            "{} {}{}{}",
+            if name == current { "*" } else { " " },
+            name,
+            if !config.get_company().is_empty() {
+                format!(": {}", config.get_company())
+            } else {
+                String::new()
+            },
+            if name == profiles.get_default() { " (default)" } else { "" },
+        );
+    }
+
+    Ok(())
+}
+
+pub fn add_profile(name: &str) -> TickResult<()> {
+    let mut profiles = config::load_profiles()?;
+
+    if !is_valid_name(name) {
+        return Err(TickError::Other("Profile names can only contain letters, digits, - and _.".to_string()));
+    }
+
+    if profiles.contains(name) {
+        println!("Profile {} already exists.", style(name).bold());
+        return Ok(());
+    }
+
+    profiles.add(name);
+    profiles.store()?;
+
+    println!(
+        "Added profile {}. Run {} to log in.",
+        style(name).bold(),
+        style(format!("tick --profile {} login", name)).bold(),
+    );
+
+    Ok(())
+}
+
+pub fn remove_profile(name: &str) -> TickResult<()> {
+    let mut profiles = config::load_profiles()?;
+
+    if name == DEFAULT_PROFILE {
+        return Err(TickError::Other("The default profile can't be removed.".to_string()));
+    }
+
+    ensure_exists(&profiles, name)?;
+
+    if !confirm_removal(name) {
+        return Err(TickError::Cancelled);
+    }
+
+    profiles.remove(name);
+    profiles.store()?;
+    config::delete(name)?;
+
+    println!("Removed profile {}. Its stored entries are left untouched.", style(name).bold());
+
+    Ok(())
+}
+
+pub fn set_default_profile(name: &str) -> TickResult<()> {
+    let mut profiles = config::load_profiles()?;
+
+    ensure_exists(&profiles, name)?;
+
+    profiles.set_default(name);
+    profiles.store()?;
+
+    println!("Using profile {} by default", style(name).bold());
+
+    Ok(())
+}
+
+fn ensure_exists(profiles: &Profiles, name: &str) -> TickResult<()> {
+    if !profiles.contains(name) {
+        return Err(TickError::Other(format!("Profile {} doesn't exist.", style(name).bold())));
+    }
+
+    Ok(())
+}
+
+fn is_valid_name(name: &str) -> bool {
+    // The profile list itself is stored next to the profile configs.
+    !name.is_empty()
+        && name != "profiles"
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// The default profile is stored without a name.
+fn profile_option(name: &str) -> Option<&str> {
+    if name == DEFAULT_PROFILE {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+fn confirm_removal(name: &str) -> bool {
+    ui::confirm(&format!("Are you sure you want to remove profile {}?", name))
+}
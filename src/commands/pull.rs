@@ -0,0 +1,148 @@
+use std::collections::{HashMap, HashSet};
+use tick_cli::{Entry, Project, TickEntry};
+use crate::{api, args::PullArgs, config::Config, error::{TickError, TickResult}, repository};
+
+pub fn pull_entries(config: &Config, args: &PullArgs) -> TickResult<()> {
+    let (from, to) = args.range.range();
+
+    let user_id = find_user_id(config, args)?;
+    let remote_entries = api::get_entries(config, &user_id, &from, &to)?;
+
+    let mut days = repository::load_entry_lists(&from, &to)?;
+    let local_ids: HashSet<u32> = days
+        .iter()
+        .flat_map(|(_, _, entries)| entries.get_all().iter().filter_map(|e| e.get_tick_id().copied()))
+        .collect();
+
+    let mut names = TaskNames::new(config);
+    let mut changed = Vec::new();
+    let mut deleted = Vec::new();
+    let mut pulled_count = 0;
+
+    for (date, path, entries) in days.iter_mut() {
+        for entry in entries.get_all() {
+            let tick_id = match entry.get_tick_id() {
+                Some(tick_id) => tick_id,
+                None => continue,
+            };
+
+            // Local changes that still have to be submitted are expected to differ.
+            if entry.should_be_updated() {
+                continue;
+            }
+
+            match remote_entries.iter().find(|r| r.get_id() == Some(*tick_id)) {
+                None => deleted.push((date.clone(), entry.clone())),
+                Some(remote) if remote.get_date() != date || remote.differs_from(entry, config.get_rounding()) => {
+                    changed.push((date.clone(), entry.clone()))
+                }
+                Some(_) => (),
+            }
+        }
+
+        let new_entries: Vec<&TickEntry> = remote_entries
+            .iter()
+            .filter(|r| r.get_date() == date)
+            .filter(|r| r.get_id().is_some_and(|id| !local_ids.contains(&id)))
+            .collect();
+
+        if new_entries.is_empty() {
+            continue;
+        }
+
+        for remote in new_entries {
+            let (project_name, task_name) = names.get(remote.get_task_id())?;
+
+            entries.add(Entry::from_tick_entry(remote, project_name, task_name));
+            pulled_count += 1;
+        }
+
+        let all_submitted = entries
+            .get_all()
+            .iter()
+            .all(|e| e.is_submitted() && !e.should_be_updated());
+        entries.set_all_submitted(all_submitted);
+
+        repository::store_entry_list(entries, path)?;
+    }
+
+    println!("Pulled {} entries", pulled_count);
+    print_flagged("Changed in Tick since they were submitted:", &changed);
+    print_flagged("Deleted from Tick:", &deleted);
+
+    Ok(())
+}
+
+/// Tick lists every user of the subscription to admins, so only go by the
+/// list when there's no doubt about who we are.
+fn find_user_id(config: &Config, args: &PullArgs) -> TickResult<u32> {
+    if let Some(user_id) = args.user.or(config.get_user_id()) {
+        return Ok(user_id);
+    }
+
+    let users = api::get_users(config)?;
+
+    match users.as_slice() {
+        [] => Err(TickError::Auth("Tick didn't list any users for this subscription.".to_string())),
+        [user] => Ok(*user.get_id()),
+        _ => Err(TickError::Other(format!(
+            "Tick lists several users for this subscription, so pass --user or set user_id in the config to one of: {}",
+            users
+                .iter()
+                .map(|u| format!("{} ({})", u.get_id(), u.get_first_name()))
+                .collect::<Vec<String>>()
+                .join(", "),
+        ))),
+    }
+}
+
+fn print_flagged(title: &str, flagged: &[(String, Entry)]) {
+    if flagged.is_empty() {
+        return;
+    }
+
+    println!("{}", title);
+    for (date, entry) in flagged {
+        println!("  {}{}", date, entry);
+    }
+}
+
+/// Looks up project and task names for the task ids of pulled entries,
+/// fetching each task only once.
+struct TaskNames<'a> {
+    config: &'a Config,
+    projects: Option<Vec<Project>>,
+    names: HashMap<u32, (Option<String>, Option<String>)>,
+}
+
+impl<'a> TaskNames<'a> {
+    fn new(config: &'a Config) -> Self {
+        Self { config, projects: None, names: HashMap::new() }
+    }
+
+    fn get(&mut self, task_id: &u32) -> TickResult<(Option<String>, Option<String>)> {
+        if let Some(names) = self.names.get(task_id) {
+            return Ok(names.clone());
+        }
+
+        let task = api::get_task(self.config, task_id)?;
+
+        let project_name = match task.get_project_id() {
+            Some(id) => self.get_projects()?.iter().find(|p| p.get_id() == id).map(|p| p.get_name().clone()),
+            None => None,
+        };
+        let names = (project_name, Some(task.get_name().clone()));
+
+        self.names.insert(*task_id, names.clone());
+
+        Ok(names)
+    }
+
+    fn get_projects(&mut self) -> TickResult<&Vec<Project>> {
+        if self.projects.is_none() {
+            self.projects = Some(api::get_projects(self.config)?);
+        }
+
+        Ok(self.projects.as_ref().unwrap())
+    }
+}
@@ -0,0 +1,101 @@
+use std::{collections::BTreeMap, iter};
+use tick_cli::{Entry, EntryKind};
+use crate::{args::{GroupBy, ReportArgs}, config::Config, error::TickResult, repository};
+
+const NO_PROJECT: &str = "(no project)";
+const NO_TASK: &str = "(no task)";
+const TOTAL: &str = "Total";
+
+/// Hours per row, per group.
+type Groups = BTreeMap<String, BTreeMap<String, Hours>>;
+
+/// Hours as they're submitted, and as they were actually booked.
+#[derive(Default, Clone, Copy)]
+struct Hours {
+    rounded: f64,
+    actual: f64,
+}
+
+impl Hours {
+    fn add(&mut self, other: Hours) {
+        self.rounded += other.rounded;
+        self.actual += other.actual;
+    }
+}
+
+pub fn report(config: &Config, args: &ReportArgs) -> TickResult<()> {
+    let rounding = config.get_rounding();
+    let (from, to) = args.range.range();
+    let mut groups = Groups::new();
+
+    for (date, _, entries) in repository::load_entry_lists(&from, &to)? {
+        // Breaks and personal time aren't worked hours.
+        for entry in entries.get_all().iter().filter(|entry| entry.get_kind() == EntryKind::Work) {
+            let (group, row) = group_keys(args.by, &date, entry);
+
+            let hours = Hours { rounded: rounding.round_hours(entry), actual: entry.calculate_hours() };
+
+            groups.entry(group).or_default().entry(row).or_default().add(hours);
+        }
+    }
+
+    if groups.is_empty() {
+        println!("No entries from {} to {}", from, to);
+        return Ok(());
+    }
+
+    println!("Hours from {} to {}\n", from, to);
+    print_groups(&groups, !rounding.is_empty());
+
+    Ok(())
+}
+
+fn group_keys(by: GroupBy, date: &str, entry: &Entry) -> (String, String) {
+    let project = entry.get_project_name().map_or(NO_PROJECT, |p| p.as_str());
+    let task = entry.get_task_name().map_or(NO_TASK, |t| t.as_str());
+
+    match by {
+        GroupBy::Project => (project.to_string(), task.to_string()),
+        GroupBy::Task => (format!("{} | {}", project, task), date.to_string()),
+        GroupBy::Day => (date.to_string(), format!("{} | {}", project, task)),
+    }
+}
+
+fn print_groups(groups: &Groups, rounded: bool) {
+    let width = groups
+        .iter()
+        .flat_map(|(group, rows)| iter::once(group.len()).chain(rows.keys().map(|row| row.len() + 2)))
+        .chain(iter::once(TOTAL.len()))
+        .max()
+        .unwrap();
+
+    // Rounded hours come first, since those are what ends up in Tick.
+    let format_hours = |hours: &Hours| {
+        if rounded {
+            format!("{:>7.2}  {:>7.2}", hours.rounded, hours.actual)
+        } else {
+            format!("{:>7.2}", hours.actual)
+        }
+    };
+
+    if rounded {
+        println!("{:<width$}  {:>7}  {:>7}", "", "Rounded", "Actual", width = width);
+    }
+
+    let mut total = Hours::default();
+
+    for (group, rows) in groups {
+        let mut subtotal = Hours::default();
+        rows.values().for_each(|hours| subtotal.add(*hours));
+
+        println!("{:<width$}  {}", group, format_hours(&subtotal), width = width);
+        for (row, hours) in rows {
+            println!("  {:<width$}  {}", row, format_hours(hours), width = width - 2);
+        }
+
+        total.add(subtotal);
+    }
+
+    println!();
+    println!("{:<width$}  {}", TOTAL, format_hours(&total), width = width);
+}
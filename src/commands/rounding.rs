@@ -0,0 +1,62 @@
+use dialoguer::console::style;
+use tick_cli::Rounding;
+use crate::{args::RoundingArgs, config::Config, error::{TickError, TickResult}};
+use super::add::find_project;
+
+pub fn list_rounding(config: &Config) -> TickResult<()> {
+    let rounding = config.get_rounding();
+
+    if rounding.is_empty() {
+        println!("Hours are submitted as they are. Run {} to round them.", style("tick rounding set").bold());
+        return Ok(());
+    }
+
+    match rounding.get_default() {
+        Some(default) => println!("All projects: {}", default),
+        None => println!("All projects: not rounded"),
+    }
+    for (project, project_rounding) in rounding.get_projects() {
+        println!("{}: {}", project, project_rounding);
+    }
+
+    Ok(())
+}
+
+pub fn set_rounding(config: &mut Config, args: &RoundingArgs) -> TickResult<()> {
+    let rounding = Rounding::create(args.mode, args.minutes);
+
+    match &args.project {
+        Some(name) => {
+            let project = find_project(config, name)?;
+            config.get_rounding_mut().set_project(project.get_name().clone(), rounding);
+            println!("Rounding hours of {} {}", style(project.get_name()).bold(), rounding);
+        }
+        None => {
+            config.get_rounding_mut().set_default(Some(rounding));
+            println!("Rounding hours of all projects {}", rounding);
+        }
+    }
+
+    config.store()?;
+
+    Ok(())
+}
+
+pub fn clear_rounding(config: &mut Config, project: Option<&String>) -> TickResult<()> {
+    match project {
+        Some(name) => {
+            if !config.get_rounding_mut().remove_project(name) {
+                return Err(TickError::Other(format!("Project {} has no rounding of its own.", style(name).bold())));
+            }
+            println!("Stopped rounding hours of {} separately", style(name).bold());
+        }
+        None => {
+            config.get_rounding_mut().set_default(None);
+            println!("Stopped rounding hours of all projects");
+        }
+    }
+
+    config.store()?;
+
+    Ok(())
+}
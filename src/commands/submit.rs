@@ -1,81 +1,328 @@
-use crate::{api, config::Config, http::HttpError, repository, ui, files};
-use chrono::NaiveTime;
-use std::{process, path::PathBuf};
+use crate::{api, args::SubmitArgs, config::{Config, MidnightPolicy}, repository, ui, files};
+use crate::error::{TickError, TickResult};
+use dialoguer::console::style;
+use chrono::{Duration, NaiveDate, NaiveTime};
+use std::path::PathBuf;
 use tick_cli::{Entry, EntryList, TickEntry, TickEntryList};
 
-pub fn submit(config: &Config) -> std::io::Result<()> {
-    let path = ui::select_file();
-    let mut entries = repository::load_entry_list(&path).expect("Cannot load entries");
+/// A day whose entries have been checked and turned into Tick entries, ready
+/// to be submitted.
+struct Day {
+    date: String,
+    path: PathBuf,
+    entries: EntryList,
+    tick_entries: TickEntryList,
+    next_day_entries: Vec<Entry>,
+}
+
+/// What submitting does with an entry in Tick.
+#[derive(PartialEq)]
+enum Action {
+    Create,
+    Update,
+    Skip,
+}
+
+impl Action {
+    fn of(entry: &Entry) -> Self {
+        if entry.should_be_updated() {
+            Action::Update
+        } else if entry.is_submitted() {
+            Action::Skip
+        } else {
+            Action::Create
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Action::Create => "create",
+            Action::Update => "update",
+            Action::Skip => "skip",
+        }
+    }
+}
+
+pub fn submit(config: &Config, args: &SubmitArgs) -> TickResult<()> {
+    if args.all_pending || args.range.is_given() {
+        return submit_days(config, args);
+    }
+
+    let path = ui::select_file()?;
+    let entries = repository::load_entry_list(&path)?;
 
     if entries.all_submitted() {
         println!("Everything up-to-date");
         return Ok(());
     }
 
-    set_entry_end_times(&mut entries);
+    let date = files::get_filename_from_path(&path).unwrap();
+    let day = prepare_day(config, date, path, entries, args)?;
 
-    if confirm_submit().is_none() {
+    if args.dry_run {
         return Ok(());
     }
 
-    submit_entries(
-        config,
-        &path,
-        &TickEntryList::from_entry_list(
-            &files::get_filename_from_path(&path).unwrap(),
-            &entries,
-        ),
-    );
+    if !args.yes && !confirm_submit() {
+        return Err(TickError::Cancelled);
+    }
+
+    store_next_day_entries(&day.date, &day.next_day_entries)?;
+    let (submitted_count, errors) = submit_entries(config, &day)?;
+
+    if let Some(error) = print_errors(errors) {
+        return Err(error);
+    }
+
+    println!("Submitted {} entries", submitted_count);
 
     Ok(())
 }
 
-fn set_entry_end_times(entries: &mut EntryList) {
-    let last_entry = entries.get_last().unwrap();
-    if last_entry.is_missing_end_time() {
+/// Submits every day in a range, or every pending day, after one
+/// confirmation for all of them.
+fn submit_days(config: &Config, args: &SubmitArgs) -> TickResult<()> {
+    let days = if args.all_pending {
+        repository::load_all_entry_lists()?
+    } else {
+        let (from, to) = args.range.range();
+        repository::load_entry_lists(&from, &to)?
+    };
+
+    let pending: Vec<(String, PathBuf, EntryList)> = days
+        .into_iter()
+        .filter(|(_, _, entries)| !entries.all_submitted() && !entries.get_all().is_empty())
+        .collect();
+
+    if pending.is_empty() {
+        println!("Everything up-to-date");
+        return Ok(());
+    }
+
+    let mut prepared: Vec<Day> = Vec::new();
+    for (date, path, mut entries) in pending {
+        // Parts after midnight of the day before go out with this day.
+        if let Some(previous) = prepared.last_mut().filter(|previous| next_date(&previous.date) == date) {
+            for entry in previous.next_day_entries.drain(..) {
+                entries.add(entry);
+            }
+        }
+
+        println!("{}", style(&date).bold());
+        prepared.push(prepare_day(config, date, path, entries, args)?);
+    }
+
+    if args.dry_run {
+        return Ok(());
+    }
+
+    if !args.yes && !confirm_submit() {
+        return Err(TickError::Cancelled);
+    }
+
+    // Keep going when a day fails, and report the first failure at the end.
+    let mut failure = None;
+    for day in &prepared {
+        store_next_day_entries(&day.date, &day.next_day_entries)?;
+        let (submitted_count, errors) = submit_entries(config, day)?;
+
+        println!("{}: Submitted {} entries", day.date, submitted_count);
+        if let Some(error) = print_errors(errors) {
+            failure.get_or_insert(error);
+        }
+    }
+
+    match failure {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// Checks the entries of a day and shows what submitting will do. A dry run
+/// shows this without asking or refusing anything.
+fn prepare_day(config: &Config, date: String, path: PathBuf, mut entries: EntryList, args: &SubmitArgs) -> TickResult<Day> {
+    set_entry_end_times(&mut entries, args.dry_run);
+    check_entries(&entries, args.force || args.dry_run)?;
+    warn_about_unbooked(&entries);
+
+    let next_day_entries = match config.get_midnight_policy() {
+        MidnightPolicy::Split => split_at_midnight(&mut entries),
+        MidnightPolicy::StartDate => Vec::new(),
+    };
+
+    let tick_entries = TickEntryList::from_entry_list(&date, &entries, config.get_rounding());
+    let day = Day { date, path, entries, tick_entries, next_day_entries };
+    print_plan(&day);
+
+    Ok(day)
+}
+
+fn set_entry_end_times(entries: &mut EntryList, dry_run: bool) {
+    // Entries of just hours don't need an end time.
+    let last_index = entries.get_all().iter().rposition(|entry| entry.has_times());
+
+    if let Some(index) = last_index.filter(|index| entries.get(*index).is_missing_end_time()) {
         println!("You didn't set an end time for the last entry of this day:");
-        println!("{}", last_entry);
+        println!("{}", entries.get(index));
         println!("This is a requirement for submitting.");
 
-        set_last_entry_end_time(entries.get_last_mut().unwrap());
+        // A dry run leaves asking for it to the real submit.
+        if !dry_run {
+            let previous = entries.get_end_time_before(index);
+            set_last_entry_end_time(entries.get_mut(index), previous);
+        }
     }
 
     entries.set_end_times();
 }
 
-fn set_last_entry_end_time(entry: &mut Entry) {
-    let end_time = input_end_time();
+/// Refuses to submit entries that would book wrong hours, unless forced.
+fn check_entries(entries: &EntryList, force: bool) -> TickResult<()> {
+    let problems = entries.validate();
+
+    for problem in &problems {
+        println!("{} {}", style("Warning:").yellow().bold(), problem);
+    }
+
+    if !force && problems.iter().any(|p| p.is_blocking()) {
+        return Err(TickError::Validation(format!(
+            "Fix these with {}, or run {} to submit anyway.",
+            style("tick edit").bold(),
+            style("tick submit --force").bold(),
+        )));
+    }
+
+    Ok(())
+}
+
+fn set_last_entry_end_time(entry: &mut Entry, previous: Option<NaiveTime>) {
+    let (end_time, next_day) = input_end_time(previous);
 
     entry.set_end_time(end_time);
+    entry.set_ends_next_day(next_day);
+}
+
+fn input_end_time(previous: Option<NaiveTime>) -> (NaiveTime, bool) {
+    ui::end_time("Input end time", None, false, previous).unwrap()
+}
+
+/// Cuts entries that cross midnight off at midnight, and returns the parts
+/// after it to be submitted with the next day.
+fn split_at_midnight(entries: &mut EntryList) -> Vec<Entry> {
+    (0..entries.get_all().len())
+        .filter_map(|index| entries.get_mut(index).split_at_midnight())
+        .collect()
+}
+
+fn next_date(date: &str) -> String {
+    let next_date = NaiveDate::parse_from_str(date, "%Y-%m-%d").expect("Cannot parse date") + Duration::days(1);
+
+    next_date.format("%Y-%m-%d").to_string()
+}
+
+fn store_next_day_entries(date: &str, next_day_entries: &[Entry]) -> TickResult<()> {
+    if next_day_entries.is_empty() {
+        return Ok(());
+    }
+
+    let next_filename = next_date(date);
+    let next_path = files::get_document_file_path_from(&next_filename)?;
+    let mut next_entries = repository::load_entry_list(&next_path)?;
+
+    for entry in next_day_entries {
+        println!("Moved the part after midnight to {}:\n{}", next_filename, entry);
+        next_entries.add(entry.clone());
+    }
+
+    repository::store_entry_list(&next_entries, &next_path)
+}
+
+/// Shows what submitting does with every entry of the day, with the hours
+/// that will be submitted next to the actual hours when they're rounded.
+fn print_plan(day: &Day) {
+    let mut total = 0.0;
+    let mut actual_total = 0.0;
+
+    println!("This will do the following in Tick:");
+
+    for tick_entry in day.tick_entries.get_all() {
+        let entry = tick_entry.get_entry().unwrap();
+        let action = Action::of(entry);
+        let hours = *tick_entry.get_hours();
+        let actual = entry.calculate_hours();
+
+        print_plan_line(action.name(), &day.date, entry, &format_hours(hours, actual));
+
+        if action != Action::Skip {
+            total += hours;
+            actual_total += actual;
+        }
+    }
+
+    // Entries that never go to Tick, like breaks and unbooked work.
+    for entry in day.entries.get_all().iter().filter(|entry| !entry.is_submittable()) {
+        print_plan_line("excluded", &day.date, entry, &format!("{:.2} hours", entry.calculate_hours()));
+    }
+
+    println!("  Total: {}", format_hours(total, actual_total));
+}
+
+fn print_plan_line(action: &str, date: &str, entry: &Entry, hours: &str) {
+    let task = match (entry.get_project_name(), entry.get_task_name()) {
+        (Some(project), Some(task)) => format!("{} | {}", project, task),
+        (Some(project), None) => project.clone(),
+        _ => entry.get_kind().to_string(),
+    };
+
+    println!("  {:<8}  {}  {:<32}  {:<24}  {}", action, date, task, hours, entry.get_notes());
 }
 
-fn input_end_time() -> NaiveTime {
-    ui::time("Input end time", None, false).unwrap()
+fn format_hours(hours: f64, actual: f64) -> String {
+    if (hours - actual).abs() < 0.005 {
+        format!("{:.2} hours", hours)
+    } else {
+        format!("{:.2} hours ({:.2} actual)", hours, actual)
+    }
 }
 
-fn confirm_submit() -> Option<bool> {
+fn confirm_submit() -> bool {
     ui::confirm("Are you sure you want to submit these entries?")
 }
 
-fn submit_entries(config: &Config, path: &PathBuf, tick_entries: &TickEntryList) {
+fn warn_about_unbooked(entries: &EntryList) {
+    let unbooked = entries.get_unbooked();
+
+    if !unbooked.is_empty() {
+        println!("These entries have no task and won't be submitted:");
+        for entry in unbooked {
+            println!("{}", entry);
+        }
+    }
+}
+
+/// Sends the day's entries to Tick and stores what was submitted, returning
+/// the number of submitted entries and the ones that couldn't be sent.
+fn submit_entries(config: &Config, day: &Day) -> TickResult<(usize, Vec<(Entry, TickError)>)> {
     let mut entries = EntryList::empty();
     let mut errors = Vec::new();
     let mut submitted_count = 0;
 
-    for tick_entry in tick_entries.get_all() {
-        let mut entry = tick_entry.get_entry().unwrap().to_owned();
+    // Keep the entries that don't go to Tick, like breaks, as they are.
+    for entry in day.entries.get_all().iter().filter(|entry| !entry.is_submittable()) {
+        entries.add(entry.clone());
+    }
 
-        if entry.is_submitted() && !entry.should_be_updated() {
-            entries.add(entry);
-            continue;
-        }
+    for tick_entry in day.tick_entries.get_all() {
+        let mut entry = tick_entry.get_entry().unwrap().to_owned();
 
-        let response: Result<TickEntry, HttpError>;
-        if entry.should_be_updated() {
-            response = api::update_entry(config, &tick_entry);
-        } else {
-            response = api::create_entry(config, &tick_entry);
-        }
+        let response: TickResult<TickEntry> = match Action::of(&entry) {
+            Action::Update => api::update_entry(config, tick_entry),
+            Action::Create => api::create_entry(config, tick_entry),
+            Action::Skip => {
+                entries.add(entry);
+                continue;
+            }
+        };
 
         match response {
             Ok(res_tick_entry) => {
@@ -83,7 +330,7 @@ fn submit_entries(config: &Config, path: &PathBuf, tick_entries: &TickEntryList)
                 entry.set_submitted_at();
                 submitted_count += 1;
             }
-            Err(e) => errors.push((tick_entry.get_entry().unwrap(), e.message().clone())),
+            Err(e) => errors.push((entry.clone(), e)),
         };
 
         entries.add(entry);
@@ -93,17 +340,22 @@ fn submit_entries(config: &Config, path: &PathBuf, tick_entries: &TickEntryList)
         entries.set_all_submitted(true);
     }
 
-    repository::store_entry_list(&entries, &path).expect("Unable to store entry list");
+    repository::store_entry_list(&entries, &day.path)?;
 
-    if !errors.is_empty() {
-        for (entry, message) in errors {
-            println!(
-                "Couldn't send the following entry:\n {}\nError: {}",
-                entry, message
-            );
-        }
-        process::exit(1);
+    Ok((submitted_count, errors))
+}
+
+/// Prints the entries that couldn't be sent, and returns the first error.
+fn print_errors(errors: Vec<(Entry, TickError)>) -> Option<TickError> {
+    let mut first = None;
+
+    for (entry, error) in errors {
+        println!(
+            "Couldn't send the following entry:\n {}\nError: {}",
+            entry, error
+        );
+        first.get_or_insert(error);
     }
 
-    println!("Submitted {} entries", submitted_count);
+    first
 }
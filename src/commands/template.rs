@@ -0,0 +1,50 @@
+use dialoguer::console::style;
+use crate::args::TemplateArgs;
+use crate::config::Config;
+use crate::error::{TickError, TickResult};
+use tick_cli::Template;
+
+use super::add::choose_project_and_task;
+
+pub fn add_template(config: &mut Config, args: &TemplateArgs) -> TickResult<()> {
+    let (project, task) = choose_project_and_task(config, args.project.as_ref(), args.task.as_ref())?;
+
+    config.add_template(Template::create(
+        args.name.clone(),
+        project.map(|p| p.get_name().clone()),
+        task.as_ref().map(|t| *t.get_id()),
+        task.map(|t| t.get_name().clone()),
+        args.notes.clone(),
+        args.duration,
+    ));
+    config.store()?;
+
+    println!("Saved template {}", style(&args.name).bold());
+
+    Ok(())
+}
+
+pub fn list_templates(config: &Config) -> TickResult<()> {
+    if config.get_templates().is_empty() {
+        println!("No templates yet. Run {} to add one.", style("tick template add <name>").bold());
+        return Ok(());
+    }
+
+    for template in config.get_templates() {
+        println!("{}", template);
+    }
+
+    Ok(())
+}
+
+pub fn remove_template(config: &mut Config, name: &str) -> TickResult<()> {
+    if !config.remove_template(name) {
+        return Err(TickError::Other(format!("Template {} doesn't exist.", style(name).bold())));
+    }
+
+    config.store()?;
+
+    println!("Removed template {}", style(name).bold());
+
+    Ok(())
+}
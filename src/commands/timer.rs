@@ -0,0 +1,103 @@
+use chrono::{Duration, DurationRound, Local, NaiveDateTime};
+use dialoguer::console::style;
+use tick_cli::Timer;
+use crate::{args::TimerArgs, config::Config, error::{TickError, TickResult}, files, repository, ui};
+use super::add::{choose_booking, input_notes, Booking};
+
+pub fn start_timer(config: &Config, args: &TimerArgs) -> TickResult<()> {
+    let running = repository::load_timer()?;
+
+    if let Some(timer) = &running {
+        println!("A timer is already running:");
+        println!("{}", timer);
+
+        if !confirm_replace() {
+            return Err(TickError::Cancelled);
+        }
+    }
+
+    start(config, args, running)
+}
+
+pub fn stop_timer() -> TickResult<()> {
+    let timer = match repository::load_timer()? {
+        Some(timer) => timer,
+        None => {
+            println!("No timer is running. Run {} to start one.", style("tick start").bold());
+            return Ok(());
+        }
+    };
+
+    store_stopped_timer(&timer, now())?;
+    repository::delete_timer()?;
+
+    Ok(())
+}
+
+pub fn switch_timer(config: &Config, args: &TimerArgs) -> TickResult<()> {
+    let running = repository::load_timer()?;
+
+    if running.is_none() {
+        return Err(TickError::Other(format!("No timer is running. Run {} to start one.", style("tick start").bold())));
+    }
+
+    start(config, args, running)
+}
+
+fn start(config: &Config, args: &TimerArgs, running: Option<Timer>) -> TickResult<()> {
+    let booking = choose_booking(
+        config,
+        args.kind,
+        args.template.as_ref(),
+        args.project.as_ref(),
+        args.task.as_ref(),
+    )?;
+    let notes = match args.notes.as_ref().or(booking.get_template_notes()) {
+        Some(notes) => notes.clone(),
+        None => input_notes(),
+    };
+
+    let Booking { kind, project_name, task_id, task_name, .. } = booking;
+
+    // Only touch the stored state once everything has been selected, so
+    // cancelling halfway leaves the running timer alone.
+    let now = now();
+    if let Some(running) = running {
+        store_stopped_timer(&running, now)?;
+    }
+
+    let mut timer = Timer::start(project_name, task_id, task_name, notes, now);
+    timer.set_kind(kind);
+    repository::store_timer(&timer)?;
+
+    println!("Started timer:");
+    println!("{}", timer);
+
+    Ok(())
+}
+
+fn store_stopped_timer(timer: &Timer, stopped_at: NaiveDateTime) -> TickResult<()> {
+    let filename = timer.get_started_at().format("%Y-%m-%d").to_string();
+    let path = files::get_document_file_path_from(&filename)?;
+    let mut entries = repository::load_entry_list(&path)?;
+
+    let entry = timer.stop(stopped_at);
+
+    println!("Stopped timer after {:.2} hours:", entry.calculate_hours());
+    println!("{}", entry);
+
+    entries.add(entry);
+
+    repository::store_entry_list(&entries, &path)
+}
+
+fn now() -> NaiveDateTime {
+    Local::now()
+        .naive_local()
+        .duration_trunc(Duration::minutes(1))
+        .unwrap()
+}
+
+fn confirm_replace() -> bool {
+    ui::confirm("Stop it and start a new one?")
+}
@@ -1,49 +1,457 @@
+use std::{cell::OnceCell, env, io::Write, process::{Command, Stdio}, time::Duration};
 use confy::ConfyError;
 use serde::{Serialize, Deserialize};
+use tick_cli::{Role, RoundingRules, Template};
+use crate::error::{TickError, TickResult};
 
 type ConfResult<T> = Result<T, ConfyError>;
 
-#[derive(Default, Debug, Serialize, Deserialize)]
+const APP_NAME: &str = "tick-cli";
+const PROFILES_NAME: &str = "profiles";
+pub const DEFAULT_PROFILE: &str = "default";
+const DEFAULT_BASE_URL: &str = "https://secure.tickspot.com";
+const BASE_URL_VAR: &str = "TICK_BASE_URL";
+const API_KEY_VAR: &str = "TICK_API_TOKEN";
+const SUBSCRIPTION_ID_VAR: &str = "TICK_SUBSCRIPTION_ID";
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    #[serde(skip)]
+    profile: Option<String>,
     version: u8,
     subscription_id: u32,
+    #[serde(skip)]
+    env_subscription_id: Option<String>,
+    #[serde(default)]
     api_key: String,
+    #[serde(default)]
+    credential_store: CredentialStore,
+    #[serde(default)]
+    api_key_command: Option<String>,
+    #[serde(default)]
+    api_key_store_command: Option<String>,
+    /// The token from the command or environment, looked up once per run.
+    #[serde(skip)]
+    external_api_key: OnceCell<String>,
+    #[serde(default)]
+    company: String,
+    #[serde(default)]
+    base_url: Option<String>,
+    #[serde(default)]
+    user_id: Option<u32>,
+    #[serde(default)]
+    midnight_policy: MidnightPolicy,
+    // Tables have to come after plain values in TOML, so keep these last,
+    // and leave out empty lists which would be written as plain values.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    subscriptions: Vec<Subscription>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    templates: Vec<Template>,
+    #[serde(default, skip_serializing_if = "RoundingRules::is_empty")]
+    rounding: RoundingRules,
+    #[serde(default, skip_serializing_if = "RetryPolicy::is_default")]
+    retry: RetryPolicy,
+}
+
+/// Which Tick date gets the hours of entries that cross midnight.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MidnightPolicy {
+    /// Book all hours on the date the entry started.
+    #[default]
+    StartDate,
+    /// Move the hours after midnight to an entry on the next date.
+    Split,
+}
+
+/// Where the API token is kept.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CredentialStore {
+    /// In the config file, as `api_key`.
+    #[default]
+    File,
+    /// Printed by `api_key_command`, like `pass show tick`. On login the
+    /// token is piped into `api_key_store_command`, if there is one.
+    Command,
+    /// In the `TICK_API_TOKEN` environment variable.
+    Env,
+}
+
+/// A subscription of the logged in account. Its token isn't kept, as
+/// switching to it asks Tick for one again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subscription {
+    subscription_id: u32,
+    company: String,
+}
+
+impl Subscription {
+    pub fn get_subscription_id(&self) -> u32 {
+        self.subscription_id
+    }
+
+    pub fn get_company(&self) -> &String {
+        &self.company
+    }
+}
+
+impl From<&Role> for Subscription {
+    fn from(role: &Role) -> Self {
+        Self {
+            subscription_id: *role.get_subscription_id(),
+            company: role.get_company().clone(),
+        }
+    }
+}
+
+/// How often and how long to wait before retrying requests that failed
+/// because Tick couldn't be reached, was overloaded or rate limited us.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_delay_ms: u64,
+    max_delay_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_retries: 3, initial_delay_ms: 500, max_delay_ms: 30_000 }
+    }
+}
+
+impl RetryPolicy {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn get_max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    pub fn get_initial_delay(&self) -> Duration {
+        Duration::from_millis(self.initial_delay_ms)
+    }
+
+    pub fn get_max_delay(&self) -> Duration {
+        Duration::from_millis(self.max_delay_ms)
+    }
 }
 
 impl Config {
-    fn load() -> ConfResult<Self> {
-        confy::load("tick-cli", None)
+    fn load(profile: Option<&str>) -> TickResult<Self> {
+        let mut config: Config = confy::load(APP_NAME, config_name(profile).as_deref())?;
+        config.profile = profile.map(String::from);
+        config.env_subscription_id = env::var(SUBSCRIPTION_ID_VAR).ok();
+
+        Ok(config)
     }
 
     pub fn store(&self) -> ConfResult<()> {
-        confy::store("tick-cli", None, self)
+        confy::store(APP_NAME, config_name(self.profile.as_deref()).as_deref(), self)
     }
 
-    pub fn reset() -> ConfResult<()> {
-        confy::store("tick-cli", None, Config::default())
+    /// Forgets the account, but keeps the rest of the settings, like where
+    /// the token is to be kept and the templates.
+    pub fn reset(&self) -> ConfResult<()> {
+        let config = Config {
+            subscription_id: 0,
+            api_key: String::new(),
+            external_api_key: OnceCell::new(),
+            company: String::new(),
+            subscriptions: Vec::new(),
+            ..self.clone()
+        };
+
+        confy::store(APP_NAME, config_name(self.profile.as_deref()).as_deref(), config)
     }
 
+    /// Whether there's no token to use, where a `TICK_API_TOKEN` in the
+    /// environment always counts, so no login is needed in CI.
     pub fn missing_api_key(&self) -> bool {
-        self.api_key.is_empty()
+        if env_api_key().is_some() {
+            return false;
+        }
+
+        match self.credential_store {
+            CredentialStore::File => self.api_key.is_empty(),
+            CredentialStore::Command => self.api_key_command.is_none(),
+            CredentialStore::Env => true,
+        }
+    }
+
+    /// The subscription in use, which `TICK_SUBSCRIPTION_ID` overrides. It's
+    /// only checked here, so commands that don't talk to Tick ignore it.
+    pub fn get_subscription_id(&self) -> TickResult<u32> {
+        match &self.env_subscription_id {
+            Some(id) => id.trim().parse().map_err(|_| {
+                TickError::Auth(format!("{} should be a number, not \"{}\"", SUBSCRIPTION_ID_VAR, id))
+            }),
+            None => Ok(self.subscription_id),
+        }
+    }
+
+    /// The API token, from `TICK_API_TOKEN` or else from wherever the
+    /// credential store keeps it.
+    pub fn get_api_key(&self) -> TickResult<String> {
+        if let Some(api_key) = env_api_key() {
+            return Ok(api_key);
+        }
+
+        if let Some(api_key) = self.external_api_key.get() {
+            return Ok(api_key.clone());
+        }
+
+        let api_key = match (self.credential_store, &self.api_key_command) {
+            (CredentialStore::File, _) => return Ok(self.api_key.clone()),
+            (CredentialStore::Env, _) => return Err(TickError::not_logged_in()),
+            (CredentialStore::Command, Some(command)) => read_api_key(command)?,
+            (CredentialStore::Command, None) => return Err(TickError::not_logged_in()),
+        };
+
+        Ok(self.external_api_key.get_or_init(|| api_key).clone())
+    }
+
+    pub fn get_credential_store(&self) -> CredentialStore {
+        self.credential_store
+    }
+
+    /// Hands the token to `api_key_store_command`, returning whether there
+    /// was one to take it. Other stores don't need this.
+    pub fn store_api_key(&self) -> TickResult<bool> {
+        match (self.credential_store, &self.api_key_store_command, self.external_api_key.get()) {
+            (CredentialStore::Command, Some(command), Some(api_key)) => {
+                write_api_key(command, api_key)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    pub fn get_company(&self) -> &String {
+        &self.company
+    }
+
+    /// The Tickspot url to talk to, which can be overridden by the
+    /// `TICK_BASE_URL` environment variable, e.g. to point at a test server.
+    pub fn get_base_url(&self) -> String {
+        env::var(BASE_URL_VAR)
+            .ok()
+            .or_else(|| self.base_url.clone())
+            .unwrap_or_else(|| DEFAULT_BASE_URL.to_string())
+            .trim_end_matches('/')
+            .to_string()
+    }
+
+    /// Which of the subscription's users is us, for when Tick lists more
+    /// than one, as it does for admins.
+    pub fn get_user_id(&self) -> Option<u32> {
+        self.user_id
+    }
+
+    pub fn get_midnight_policy(&self) -> MidnightPolicy {
+        self.midnight_policy
+    }
+
+    pub fn get_rounding(&self) -> &RoundingRules {
+        &self.rounding
+    }
+
+    pub fn get_rounding_mut(&mut self) -> &mut RoundingRules {
+        &mut self.rounding
+    }
+
+    pub fn get_retry(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    pub fn get_subscriptions(&self) -> &Vec<Subscription> {
+        &self.subscriptions
+    }
+
+    /// Remembers the subscriptions of the roles to switch between, without
+    /// their tokens.
+    pub fn set_subscriptions(&mut self, roles: &[Role]) {
+        self.subscriptions = roles.iter().map(Subscription::from).collect();
+    }
+
+    pub fn get_templates(&self) -> &Vec<Template> {
+        &self.templates
+    }
+
+    pub fn find_template(&self, name: &str) -> Option<&Template> {
+        self.templates.iter().find(|t| t.get_name() == name)
+    }
+
+    /// Adds a template, replacing any with the same name.
+    pub fn add_template(&mut self, template: Template) {
+        self.remove_template(template.get_name().clone().as_str());
+        self.templates.push(template);
+        self.templates.sort_by(|a, b| a.get_name().cmp(b.get_name()));
     }
 
-    pub fn get_subscription_id(&self) -> &u32 {
-        &self.subscription_id
+    pub fn remove_template(&mut self, name: &str) -> bool {
+        let count = self.templates.len();
+        self.templates.retain(|t| t.get_name() != name);
+
+        self.templates.len() < count
     }
 
-    pub fn get_api_key(&self) -> &String {
-        &self.api_key
+    pub fn use_role(&mut self, role: &Role) {
+        self.use_token(*role.get_subscription_id(), role.get_api_token());
+        self.company = role.get_company().clone();
     }
 
-    pub fn set_subscription_id(&mut self, subscription_id: u32) {
+    /// Uses a token for a subscription without knowing the company it's of.
+    pub fn use_token(&mut self, subscription_id: u32, api_token: &str) {
         self.subscription_id = subscription_id;
+        self.company = String::new();
+
+        if self.credential_store == CredentialStore::File {
+            self.api_key = api_token.to_string();
+        } else {
+            self.external_api_key = OnceCell::from(api_token.to_string());
+        }
     }
+}
+
+/// Names of the profiles next to the default one, and which of them is
+/// used when no profile is given.
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct Profiles {
+    default: Option<String>,
+    names: Vec<String>,
+}
+
+impl Profiles {
+    fn load() -> ConfResult<Self> {
+        confy::load(APP_NAME, PROFILES_NAME)
+    }
+
+    pub fn store(&self) -> ConfResult<()> {
+        confy::store(APP_NAME, PROFILES_NAME, self)
+    }
+
+    pub fn get_default(&self) -> &str {
+        self.default.as_deref().unwrap_or(DEFAULT_PROFILE)
+    }
+
+    pub fn get_names(&self) -> Vec<&str> {
+        let mut names = vec![DEFAULT_PROFILE];
+        names.extend(self.names.iter().map(|n| n.as_str()));
+
+        names
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get_names().contains(&name)
+    }
+
+    pub fn add(&mut self, name: &str) {
+        self.names.push(name.to_string());
+        self.names.sort();
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.names.retain(|n| n != name);
 
-    pub fn set_api_key(&mut self, api_key: String) {
-        self.api_key = api_key;
+        if self.default.as_deref() == Some(name) {
+            self.default = None;
+        }
     }
+
+    pub fn set_default(&mut self, name: &str) {
+        self.default = if name == DEFAULT_PROFILE {
+            None
+        } else {
+            Some(name.to_string())
+        };
+    }
+}
+
+fn env_api_key() -> Option<String> {
+    env::var(API_KEY_VAR).ok().filter(|api_key| !api_key.trim().is_empty())
+}
+
+/// Runs the command through the shell and takes the first line it prints.
+fn read_api_key(command: &str) -> TickResult<String> {
+    let output = shell(command)
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| command_error(command, &e.to_string()))?;
+
+    if !output.status.success() {
+        return Err(command_error(command, &output.status.to_string()));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    match stdout.lines().next().map(str::trim) {
+        Some(api_key) if !api_key.is_empty() => Ok(api_key.to_string()),
+        _ => Err(command_error(command, "no token printed")),
+    }
+}
+
+/// Runs the command through the shell with the token on its stdin.
+fn write_api_key(command: &str, api_key: &str) -> TickResult<()> {
+    let mut child = shell(command)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| command_error(command, &e.to_string()))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        writeln!(stdin, "{}", api_key).map_err(|e| command_error(command, &e.to_string()))?;
+    }
+
+    let status = child.wait().map_err(|e| command_error(command, &e.to_string()))?;
+    if !status.success() {
+        return Err(command_error(command, &status.to_string()));
+    }
+
+    Ok(())
+}
+
+fn shell(command: &str) -> Command {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell.arg(command);
+
+    shell
+}
+
+fn command_error(command: &str, reason: &str) -> TickError {
+    TickError::Auth(format!("Credential command `{}` failed: {}", command, reason))
 }
 
-pub fn load() -> ConfResult<Config> {
-    Config::load()
+/// Names the config of a profile `<profile>-config`, like confy names the
+/// default one, so no profile can take the place of the profiles list.
+fn config_name(profile: Option<&str>) -> Option<String> {
+    profile.map(|profile| format!("{}-config", profile))
+}
+
+/// Loads the config of a profile, where `None` is the default profile.
+pub fn load(profile: Option<&str>) -> TickResult<Config> {
+    Config::load(profile)
+}
+
+pub fn load_profiles() -> ConfResult<Profiles> {
+    Profiles::load()
+}
+
+/// Deletes the stored config of a profile.
+pub fn delete(profile: &str) -> ConfResult<()> {
+    let path = confy::get_configuration_file_path(APP_NAME, config_name(Some(profile)).as_deref())?;
+
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(ConfyError::GeneralLoadError(e)),
+    }
 }
@@ -0,0 +1,84 @@
+use std::fmt;
+use confy::ConfyError;
+use dialoguer::console::style;
+use crate::files::FileError;
+
+pub type TickResult<T> = Result<T, TickError>;
+
+/// Everything that can make a command fail, each kind with its own exit code
+/// so scripts can tell them apart.
+#[derive(Debug)]
+pub enum TickError {
+    /// Not logged in, or Tick rejected the credentials.
+    Auth(String),
+    /// Tick couldn't be reached, or failed to handle a request.
+    Network(String),
+    /// Tick or a local check refused the entries as they are.
+    Validation(String),
+    /// A file or the config couldn't be read or written.
+    Storage(String),
+    /// A file or response couldn't be understood.
+    Parse(String),
+    /// The user declined to continue.
+    Cancelled,
+    /// Anything else, like an unknown profile or template.
+    Other(String),
+}
+
+impl TickError {
+    pub fn not_logged_in() -> Self {
+        TickError::Auth(format!(
+            "To get started with Tick CLI, please run {}, or set {} and {}",
+            style("tick login").bold(),
+            style("TICK_API_TOKEN").bold(),
+            style("TICK_SUBSCRIPTION_ID").bold(),
+        ))
+    }
+
+    /// 2 is left for wrong arguments, which clap exits with.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            TickError::Other(_) => 1,
+            TickError::Auth(_) => 3,
+            TickError::Network(_) => 4,
+            TickError::Validation(_) => 5,
+            TickError::Storage(_) => 6,
+            TickError::Parse(_) => 7,
+            TickError::Cancelled => 8,
+        }
+    }
+}
+
+impl fmt::Display for TickError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TickError::Auth(message)
+            | TickError::Network(message)
+            | TickError::Validation(message)
+            | TickError::Storage(message)
+            | TickError::Parse(message)
+            | TickError::Other(message) => write!(f, "{}", message),
+            TickError::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for TickError {}
+
+impl From<FileError> for TickError {
+    fn from(error: FileError) -> Self {
+        TickError::Storage(error.to_string())
+    }
+}
+
+impl From<std::io::Error> for TickError {
+    fn from(error: std::io::Error) -> Self {
+        TickError::Storage(error.to_string())
+    }
+}
+
+impl From<ConfyError> for TickError {
+    fn from(error: ConfyError) -> Self {
+        TickError::Storage(format!("Cannot read or write the config: {}", error))
+    }
+}
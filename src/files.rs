@@ -1,10 +1,15 @@
+#[allow(clippy::single_component_path_imports)]
 use dirs;
-use std::fs;
+use std::{env, fmt, fs};
 use std::io::ErrorKind;
 use std::path::PathBuf;
 use std::result::Result;
+use std::sync::OnceLock;
 
 const BASE_DIR: &str = "Tick";
+const DATA_DIR_VAR: &str = "TICK_DATA_DIR";
+
+static PROFILE: OnceLock<String> = OnceLock::new();
 
 #[derive(Debug)]
 pub enum FileError {
@@ -18,6 +23,15 @@ impl FileError {
     }
 }
 
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FileError::IoError(error) => write!(f, "{}", error),
+            FileError::FileError(message) => write!(f, "{}", message),
+        }
+    }
+}
+
 impl From<std::io::Error> for FileError {
     fn from(error: std::io::Error) -> Self {
         FileError::IoError(error)
@@ -27,23 +41,52 @@ impl From<std::io::Error> for FileError {
 enum Dir {
     Document,
     Cache,
+    Data,
 }
 
 impl Dir {
-    fn base(&self) -> PathBuf {
+    /// `TICK_DATA_DIR` replaces the dir with the entries, for instance to
+    /// point a CI job at a checkout of them. Other profiles than the default
+    /// one get a dir next to it, like `entries-work`.
+    fn base(&self) -> Result<PathBuf, FileError> {
+        if let (Self::Document, Some(mut dir)) = (self, env::var_os(DATA_DIR_VAR).filter(|d| !d.is_empty())) {
+            if let Some(profile) = PROFILE.get() {
+                dir.push(format!("-{}", profile));
+            }
+
+            return Ok(PathBuf::from(dir));
+        }
+
         let mut path = match self {
-            Self::Document => dirs::document_dir().expect("Default document dir not found"),
-            Self::Cache => dirs::cache_dir().expect("Default cache dir not found"),
+            Self::Document => dirs::document_dir().ok_or(FileError::new("Default document dir not found"))?,
+            Self::Cache => dirs::cache_dir().ok_or(FileError::new("Default cache dir not found"))?,
+            Self::Data => dirs::data_local_dir().ok_or(FileError::new("Default data dir not found"))?,
         };
 
-        path.push(BASE_DIR);
+        path.push(base_dir_name());
 
-        path
+        Ok(path)
+    }
+}
+
+/// Gives every profile other than the default one its own base dirs, like
+/// `Tick-work`, so their entries, cache and timer never mix. Must be called
+/// before any file is touched; `None` selects the default profile.
+pub fn set_profile(profile: Option<&str>) {
+    if let Some(profile) = profile {
+        PROFILE.set(profile.to_string()).expect("Profile can only be set once");
+    }
+}
+
+fn base_dir_name() -> String {
+    match PROFILE.get() {
+        Some(profile) => format!("{}-{}", BASE_DIR, profile),
+        None => BASE_DIR.to_string(),
     }
 }
 
 pub fn read_from_documents(path: &PathBuf) -> Result<String, FileError> {
-    if !ensure_path_has_base(&Dir::Document, path) {
+    if !ensure_path_has_base(&Dir::Document, path)? {
         return Err(FileError::new("Path doesn't start with document dir"));
     }
 
@@ -51,15 +94,23 @@ pub fn read_from_documents(path: &PathBuf) -> Result<String, FileError> {
 }
 
 pub fn read_from_cache(path: &PathBuf) -> Result<String, FileError> {
-    if !ensure_path_has_base(&Dir::Cache, path) {
+    if !ensure_path_has_base(&Dir::Cache, path)? {
         return Err(FileError::new("Path doesn't start with cache dir"));
     }
 
     read(path)
 }
 
+pub fn read_from_data(path: &PathBuf) -> Result<String, FileError> {
+    if !ensure_path_has_base(&Dir::Data, path)? {
+        return Err(FileError::new("Path doesn't start with data dir"));
+    }
+
+    read(path)
+}
+
 pub fn write_to_documents(path: &PathBuf, content: String) -> Result<(), FileError> {
-    if !ensure_path_has_base(&Dir::Document, path) {
+    if !ensure_path_has_base(&Dir::Document, path)? {
         return Err(FileError::new("Path doesn't start with document dir"));
     }
 
@@ -67,27 +118,43 @@ pub fn write_to_documents(path: &PathBuf, content: String) -> Result<(), FileErr
 }
 
 pub fn write_to_cache(path: &PathBuf, content: String) -> Result<(), FileError> {
-    if !ensure_path_has_base(&Dir::Cache, path) {
+    if !ensure_path_has_base(&Dir::Cache, path)? {
         return Err(FileError::new("Path doesn't start with cache dir"));
     }
 
     write(path, content)
 }
 
+pub fn write_to_data(path: &PathBuf, content: String) -> Result<(), FileError> {
+    if !ensure_path_has_base(&Dir::Data, path)? {
+        return Err(FileError::new("Path doesn't start with data dir"));
+    }
+
+    write(path, content)
+}
+
 pub fn delete_documents(path: &PathBuf) -> Result<(), FileError> {
-    if !ensure_path_has_base(&Dir::Document, path) {
+    if !ensure_path_has_base(&Dir::Document, path)? {
         return Err(FileError::new("Path doesn't start with document dir"));
     }
 
     delete(path)
 }
 
+pub fn delete_data(path: &PathBuf) -> Result<(), FileError> {
+    if !ensure_path_has_base(&Dir::Data, path)? {
+        return Err(FileError::new("Path doesn't start with data dir"));
+    }
+
+    delete(path)
+}
+
 fn read(file: &PathBuf) -> Result<String, FileError> {
     fs::read_to_string(file).map_err(FileError::from)
 }
 
 fn write(path: &PathBuf, content: String) -> Result<(), FileError> {
-    ensure_path_exists(path);
+    ensure_path_exists(path)?;
 
     fs::write(path, content).map_err(FileError::from)
 }
@@ -96,17 +163,21 @@ fn delete(path: &PathBuf) -> Result<(), FileError> {
     fs::remove_file(path).map_err(FileError::from)
 }
 
-pub fn get_document_file_path(path: Option<&PathBuf>, child: Option<&String>) -> PathBuf {
-    get_file_path(Some(&Dir::Document), path, child).unwrap()
+pub fn get_document_file_path(path: Option<&PathBuf>, child: Option<&String>) -> Result<PathBuf, FileError> {
+    get_file_path(Some(&Dir::Document), path, child)
+}
+
+pub fn get_cache_file_path(path: Option<&PathBuf>, child: Option<&String>) -> Result<PathBuf, FileError> {
+    get_file_path(Some(&Dir::Cache), path, child)
 }
 
-pub fn get_cache_file_path(path: Option<&PathBuf>, child: Option<&String>) -> PathBuf {
-    get_file_path(Some(&Dir::Cache), path, child).unwrap()
+pub fn get_data_file_path(path: Option<&PathBuf>, child: Option<&String>) -> Result<PathBuf, FileError> {
+    get_file_path(Some(&Dir::Data), path, child)
 }
 
-pub fn get_file_names(path: &PathBuf) -> Vec<String> {
-    let mut file_names = fs::read_dir(path)
-        .unwrap()
+#[allow(clippy::redundant_closure)]
+pub fn get_file_names(path: &PathBuf) -> Result<Vec<String>, FileError> {
+    let mut file_names = fs::read_dir(path)?
         .filter_map(|file| {
             file.ok().and_then(|e| {
                 e.path()
@@ -118,23 +189,36 @@ pub fn get_file_names(path: &PathBuf) -> Vec<String> {
 
     file_names.sort_by(|a, b| b.cmp(a));
 
-    file_names
+    Ok(file_names)
 }
 
-pub fn get_document_file_path_from(filename: &String) -> Result<PathBuf, &'static str> {
+/// The paths of everything in a dir, sorted.
+pub fn get_paths(path: &PathBuf) -> Result<Vec<PathBuf>, FileError> {
+    let mut paths = fs::read_dir(path)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<PathBuf>, std::io::Error>>()?;
+
+    paths.sort();
+
+    Ok(paths)
+}
+
+pub fn get_document_file_path_from(filename: &String) -> Result<PathBuf, FileError> {
     let splitted = filename.split('-').collect::<Vec<&str>>();
-    let path: PathBuf = splitted[..2].iter().collect();
+    let path: PathBuf = splitted
+        .get(..2)
+        .ok_or(FileError::new("Filename isn't a date"))?
+        .iter()
+        .collect();
 
-    match get_file_path(Some(&Dir::Document), Some(&path), None) {
-        Err(err) => Err(err),
-        Ok(mut path_buf) => {
-            path_buf.push(PathBuf::from(filename));
-            path_buf.set_extension("json");
-            Ok(path_buf)
-        }
-    }
+    let mut path_buf = get_file_path(Some(&Dir::Document), Some(&path), None)?;
+    path_buf.push(PathBuf::from(filename));
+    path_buf.set_extension("json");
+
+    Ok(path_buf)
 }
 
+#[allow(clippy::ptr_arg, clippy::redundant_closure)]
 pub fn get_filename_from_path(path: &PathBuf) -> Option<String> {
     path.file_stem()
         .and_then(|s| s.to_str().map(|s| String::from(s)))
@@ -144,16 +228,16 @@ fn get_file_path(
     dir: Option<&Dir>,
     path: Option<&PathBuf>,
     child: Option<&String>,
-) -> Result<PathBuf, &'static str> {
+) -> Result<PathBuf, FileError> {
     let path_result = match (dir, path) {
-        (Some(dir), None) => Ok(dir.base()),
+        (Some(dir), None) => dir.base(),
         (None, Some(path)) => Ok(path.to_path_buf()),
         (Some(dir), Some(path)) => {
-            let mut _path = dir.base();
+            let mut _path = dir.base()?;
             _path.push(path);
             Ok(_path)
         },
-        (None, None) => Err("Either dir or path should be provided")
+        (None, None) => Err(FileError::new("Either dir or path should be provided"))
     };
 
     match path_result {
@@ -168,16 +252,20 @@ fn get_file_path(
     }
 }
 
-fn ensure_path_has_base(dir: &Dir, path: &PathBuf) -> bool {
-    path.as_path().starts_with(dir.base())
+#[allow(clippy::ptr_arg)]
+fn ensure_path_has_base(dir: &Dir, path: &PathBuf) -> Result<bool, FileError> {
+    Ok(path.as_path().starts_with(dir.base()?))
 }
 
-fn ensure_path_exists(path: &PathBuf) {
+#[allow(clippy::ptr_arg)]
+fn ensure_path_exists(path: &PathBuf) -> Result<(), FileError> {
     if let Some(parent) = path.parent() {
         match fs::create_dir_all(parent) {
             Ok(()) => (),
             Err(e) if e.kind() == ErrorKind::AlreadyExists => (),
-            Err(_) => panic!("Cannot create base dir"),
+            Err(e) => return Err(FileError::from(e)),
         }
-    } 
+    }
+
+    Ok(())
 }
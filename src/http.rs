@@ -1,94 +1,154 @@
 use crate::{
     cache::{Cache, CachedResponse},
-    config::Config,
+    config::{Config, RetryPolicy},
+    error::{TickError, TickResult},
 };
 use reqwest::{
     blocking::{Client, RequestBuilder, Response},
     Method,
 };
 use serde::{de::DeserializeOwned, Serialize};
-use std::process;
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    thread,
+    time::Duration,
+};
 use reqwest::header::{self};
 
 const USER_AGENT: &str = "tick-cli (auke@ijsfontein.nl)";
+const PAGE_SIZE: usize = 100;
+const MAX_PAGES: u32 = 100;
 
-#[derive(Debug)]
-pub struct HttpError {
-    code: u16,
-    message: String,
+fn connection_error() -> TickError {
+    TickError::Network("Error connecting to Tickspot.\nPlease check your internet connection.".to_string())
 }
 
-impl HttpError {
-    pub fn is_unauthenticated_error(&self) -> bool {
-        self.code == 401
-    }
+fn invalid_response() -> TickError {
+    TickError::Parse("Unexpected response from Tickspot.".to_string())
+}
+
+fn error_from_response(response: Response) -> TickError {
+    let code = response.status().as_u16();
+    let message = match response.text() {
+        Ok(text) if !text.trim().is_empty() => text,
+        _ => format!("Tickspot responded with status {}", code),
+    };
 
-    pub fn message(&self) -> &String {
-        &self.message
+    match code {
+        401 => TickError::Auth(message),
+        400..=499 => TickError::Validation(message),
+        _ => TickError::Network(message),
     }
 }
 
+/// Credentials to use instead of the API token of the config.
+enum Credentials<'a> {
+    Basic(&'a String, &'a String),
+    Token(&'a str),
+}
+
 pub fn get_basic_auth<T: DeserializeOwned + Serialize + Clone>(
+    config: &Config,
     credentials: (&String, &String),
     url: &String,
-) -> Result<T, HttpError> {
-    call(None, Method::GET, url, None, false, Some(credentials))
+) -> TickResult<T> {
+    call(config, Method::GET, url, None, false, Some(Credentials::Basic(credentials.0, credentials.1)))
+}
+
+/// Gets with a token that isn't in use yet, like to check it. It's not
+/// cached, as the cache doesn't tell tokens apart.
+pub fn get_with_token<T: DeserializeOwned + Serialize + Clone>(
+    config: &Config,
+    api_token: &str,
+    url: &String,
+) -> TickResult<T> {
+    call(config, Method::GET, url, None, false, Some(Credentials::Token(api_token)))
 }
 
 pub fn get<T: DeserializeOwned + Serialize + Clone>(
     config: &Config,
     url: &String,
-) -> Result<T, HttpError> {
-    call(Some(config), Method::GET, url, None, true, None)
+) -> TickResult<T> {
+    call(config, Method::GET, url, None, true, None)
+}
+
+/// Fetches every page of a paginated endpoint, walking `?page=N` until a
+/// page isn't full. Each page is cached on its own. Gives up after
+/// `MAX_PAGES`, in case something in between ignores the page.
+pub fn get_all<T: DeserializeOwned + Serialize + Clone>(
+    config: &Config,
+    url: &String,
+) -> TickResult<Vec<T>> {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    let mut items = Vec::new();
+
+    for page in 1..=MAX_PAGES {
+        let page_url = format!("{}{}page={}", url, separator, page);
+        let mut page_items: Vec<T> = get(config, &page_url)?;
+        let is_last = page_items.len() < PAGE_SIZE;
+
+        items.append(&mut page_items);
+
+        if is_last {
+            return Ok(items);
+        }
+    }
+
+    Err(TickError::Parse(format!("Tickspot kept sending pages of {} after page {}.", url, MAX_PAGES)))
 }
 
 pub fn post<T: DeserializeOwned + Serialize + Clone>(
     config: &Config,
     url: &String,
     body: &T,
-) -> Result<T, HttpError> {
-    call(Some(config), Method::POST, url, Some(body), false, None)
+) -> TickResult<T> {
+    call(config, Method::POST, url, Some(body), false, None)
 }
 
 pub fn put<T: DeserializeOwned + Serialize + Clone>(
     config: &Config,
     url: &String,
     body: &T,
-) -> Result<T, HttpError> {
-    call(Some(config), Method::PUT, url, Some(body), false, None)
+) -> TickResult<T> {
+    call(config, Method::PUT, url, Some(body), false, None)
 }
 
-pub fn delete<T: DeserializeOwned + Serialize + Clone>(
-    config: &Config,
-    url: &String,
-) -> Result<T, HttpError> {
-    call(Some(config), Method::DELETE, url, None, false, None)
+/// Deletes a resource. Tick responds without a body, so there's nothing to
+/// return.
+pub fn delete(config: &Config, url: &String) -> TickResult<()> {
+    let response = send(config, &Method::DELETE, || {
+        Ok(set_user_agent(enable_auth(config, None, construct_request(Method::DELETE, url))?))
+    })?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(error_from_response(response))
+    }
 }
 
 fn call<T: DeserializeOwned + Serialize + Clone>(
-    config: Option<&Config>,
+    config: &Config,
     method: Method,
     url: &String,
     body: Option<&T>,
     should_cache: bool,
-    credentials: Option<(&String, &String)>,
-) -> Result<T, HttpError> {
+    credentials: Option<Credentials>,
+) -> TickResult<T> {
     let mut cache = Cache::<T>::new();
 
-    let mut request = construct_request(method, url);
-    request = enable_auth(config, credentials, request);
-    request = set_headers(request, &cache, url);
-
-    if let Some(body) = body {
-        request = request.json(body);
-    }
+    let response = send(config, &method, || {
+        let mut request = construct_request(method.clone(), url);
+        request = enable_auth(config, credentials.as_ref(), request)?;
+        request = set_headers(request, &cache, url);
 
-    let response = request.send();
+        if let Some(body) = body {
+            request = request.json(body);
+        }
 
-    if response.is_err() {
-        println!("Error connecting to Tickspot.\nPlease check your internet connection.");
-        process::exit(1);
-    }
+        Ok(request)
+    })?;
 
     let cache_option = if should_cache {
         Some(&mut cache)
@@ -96,7 +156,66 @@ fn call<T: DeserializeOwned + Serialize + Clone>(
         None
     };
 
-    handle_response(response.unwrap(), cache_option)
+    handle_response(response, cache_option)
+}
+
+/// Sends a request, retrying with exponential backoff when Tick can't be
+/// reached, is overloaded or rate limits us. Creating entries isn't
+/// idempotent, so those are only retried when Tick can't have handled them.
+fn send(
+    config: &Config,
+    method: &Method,
+    request: impl Fn() -> TickResult<RequestBuilder>,
+) -> TickResult<Response> {
+    let policy = config.get_retry();
+    let idempotent = *method != Method::POST;
+
+    let mut attempt = 0;
+
+    loop {
+        let result = request()?.send();
+
+        let delay = match &result {
+            Err(e) if e.is_connect() || (idempotent && e.is_timeout()) => Some(backoff(policy, attempt)),
+            Err(_) => None,
+            Ok(response) => match response.status().as_u16() {
+                429 | 503 => Some(retry_after(response).unwrap_or_else(|| backoff(policy, attempt))),
+                500..=599 if idempotent => Some(backoff(policy, attempt)),
+                _ => None,
+            },
+        };
+
+        match delay {
+            Some(delay) if attempt < policy.get_max_retries() => {
+                thread::sleep(delay.min(policy.get_max_delay()));
+                attempt += 1;
+            }
+            _ => return result.map_err(|_| connection_error()),
+        }
+    }
+}
+
+/// Doubles the delay on every attempt, picking a random one between half and
+/// all of it so clients don't retry in lockstep.
+fn backoff(policy: &RetryPolicy, attempt: u32) -> Duration {
+    let delay = policy
+        .get_initial_delay()
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(policy.get_max_delay());
+    let jitter = RandomState::new().build_hasher().finish() % 1000;
+
+    delay / 2 + delay / 2 * jitter as u32 / 1000
+}
+
+/// The delay Tick asks for, in seconds. Dates aren't supported, in which case
+/// the regular backoff is used.
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok())
+        .map(Duration::from_secs)
 }
 
 fn construct_request(method: Method, url: &String) -> RequestBuilder {
@@ -104,25 +223,28 @@ fn construct_request(method: Method, url: &String) -> RequestBuilder {
 }
 
 fn enable_auth(
-    config: Option<&Config>,
-    credentials: Option<(&String, &String)>,
+    config: &Config,
+    credentials: Option<&Credentials>,
     client: RequestBuilder,
-) -> RequestBuilder {
-    match config {
-        Some(config) => client.bearer_auth(config.get_api_key()),
-        None => match credentials {
-            Some((username, password)) => client.basic_auth(username, Some(password)),
-            None => client,
-        },
+) -> TickResult<RequestBuilder> {
+    match credentials {
+        Some(Credentials::Basic(username, password)) => Ok(client.basic_auth(username, Some(password))),
+        Some(Credentials::Token(api_token)) => Ok(client.bearer_auth(api_token)),
+        None => Ok(client.bearer_auth(config.get_api_key()?)),
     }
 }
 
+fn set_user_agent(request: RequestBuilder) -> RequestBuilder {
+    request.header(header::USER_AGENT, USER_AGENT)
+}
+
+#[allow(clippy::ptr_arg)]
 fn set_headers<T: DeserializeOwned + Serialize + Clone>(
     request: RequestBuilder,
     cache: &Cache<T>,
     url: &String,
 ) -> RequestBuilder {
-    let mut _request = request.header(header::USER_AGENT, USER_AGENT);
+    let mut _request = set_user_agent(request);
 
     if let Some(cached_response) = cache.get(url.clone()) {
         if let Some(etag) = cached_response.get_etag() {
@@ -139,37 +261,27 @@ fn set_headers<T: DeserializeOwned + Serialize + Clone>(
 fn handle_response<T: DeserializeOwned + Serialize + Clone>(
     response: Response,
     cache_option: Option<&mut Cache<T>>,
-) -> Result<T, HttpError> {
+) -> TickResult<T> {
     match response.status().as_u16() {
         304 => {
-            let cached_response = cache_option.unwrap().get(response.url().to_string().clone()).unwrap();
+            let cached_response = cache_option
+                .and_then(|cache| cache.get(response.url().to_string()))
+                .ok_or_else(invalid_response)?;
 
-            return Ok(cached_response.get_json().clone());
-        }
-        200..=299 => {
-            let json: T;
-            if let Some(cache) = cache_option {
-                json = cache_response(cache, response);
-            } else {
-                json = response.json().expect("Unable to convert response to json");
-            }
-
-            Ok(json)
-        }
-        400..=499 => Err(HttpError {
-            code: response.status().as_u16(),
-            message: response.text().unwrap(),
-        }),
-        _ => {
-            panic!("Unexpected status code: {}", response.status());
+            Ok(cached_response.get_json().clone())
         }
+        200..=299 => match cache_option {
+            Some(cache) => cache_response(cache, response),
+            None => response.json().map_err(|_| invalid_response()),
+        },
+        _ => Err(error_from_response(response)),
     }
 }
 
 fn cache_response<T: DeserializeOwned + Serialize + Clone>(
     cache: &mut Cache<T>,
     response: Response,
-) -> T {
+) -> TickResult<T> {
     let etag = response
         .headers()
         .get(header::ETAG)
@@ -184,12 +296,12 @@ fn cache_response<T: DeserializeOwned + Serialize + Clone>(
 
     let url = response.url().clone();
 
-    let json: T = response.json().expect("Unable to convert response to json");
+    let json: T = response.json().map_err(|_| invalid_response())?;
 
     cache.set(
         url.to_string(),
         CachedResponse::new(etag, last_modified, json.clone()),
     );
 
-    json
+    Ok(json)
 }
@@ -1,5 +1,6 @@
 use core::fmt;
-use chrono::{NaiveTime, NaiveDateTime, Utc};
+use std::str::FromStr;
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, NaiveDateTime, Utc, Weekday};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -25,10 +26,15 @@ impl Role {
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct User {
+    id: u32,
     first_name: String,
 }
 
 impl User {
+    pub fn get_id(&self) -> &u32 {
+        &self.id
+    }
+
     pub fn get_first_name(&self) -> &String {
         &self.first_name
     }
@@ -54,6 +60,7 @@ impl Project {
 pub struct Task {
     id: u32,
     name: String,
+    project_id: Option<u32>,
 }
 
 impl Task {
@@ -61,19 +68,98 @@ impl Task {
         &self.id
     }
 
+    pub fn get_project_id(&self) -> Option<&u32> {
+        self.project_id.as_ref()
+    }
+
     pub fn get_name(&self) -> &String {
         &self.name
     }
 }
 
-#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[derive(Debug, PartialEq)]
+pub enum NameMatchError {
+    NotFound,
+    Ambiguous(Vec<String>),
+}
+
+/// Finds `query` in `names`, either by an exact match or by a unique
+/// case-insensitive partial match.
+pub fn find_by_name(names: &[String], query: &str) -> Result<usize, NameMatchError> {
+    if let Some(index) = names.iter().position(|name| name == query) {
+        return Ok(index);
+    }
+
+    let query = query.to_lowercase();
+    if let Some(index) = names.iter().position(|name| name.to_lowercase() == query) {
+        return Ok(index);
+    }
+
+    let matches: Vec<usize> = names
+        .iter()
+        .enumerate()
+        .filter(|(_, name)| name.to_lowercase().contains(&query))
+        .map(|(index, _)| index)
+        .collect();
+
+    match matches.len() {
+        0 => Err(NameMatchError::NotFound),
+        1 => Ok(matches[0]),
+        _ => Err(NameMatchError::Ambiguous(
+            matches.iter().map(|index| names[*index].clone()).collect(),
+        )),
+    }
+}
+
+/// What an entry stands for. Only work is submitted to Tick.
+#[derive(Serialize, Deserialize, Default, Eq, PartialEq, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    #[default]
+    Work,
+    Break,
+    Personal,
+}
+
+impl FromStr for EntryKind {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_lowercase().as_str() {
+            "work" => Ok(EntryKind::Work),
+            "break" => Ok(EntryKind::Break),
+            "personal" => Ok(EntryKind::Personal),
+            _ => Err("Unknown kind. Please provide work, break or personal".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for EntryKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EntryKind::Work => write!(f, "Work"),
+            EntryKind::Break => write!(f, "Break"),
+            EntryKind::Personal => write!(f, "Personal"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct Entry {
     tick_id: Option<u32>,
+    #[serde(default)]
+    kind: EntryKind,
     project_name: Option<String>,
     task_id: Option<u32>,
     task_name: Option<String>,
-    start_time: NaiveTime,
+    /// Entries logged as just a number of hours have no times.
+    start_time: Option<NaiveTime>,
     end_time: Option<NaiveTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hours: Option<f64>,
+    /// The end time is on the day after the start time.
+    #[serde(default)]
+    ends_next_day: bool,
     notes: String,
     submitted_at: Option<NaiveDateTime>,
     updated_at: Option<NaiveDateTime>,
@@ -90,17 +176,67 @@ impl Entry {
     ) -> Self {
         Self {
             tick_id: None,
+            kind: EntryKind::Work,
             project_name,
             task_id,
             task_name,
-            start_time,
+            start_time: Some(start_time),
             end_time,
+            hours: None,
+            ends_next_day: false,
+            notes,
+            submitted_at: None,
+            updated_at: None,
+        }
+    }
+
+    /// Creates an entry of just a number of hours, without times.
+    pub fn create_with_hours(
+        project_name: Option<String>,
+        task_id: Option<u32>,
+        task_name: Option<String>,
+        hours: f64,
+        notes: String,
+    ) -> Self {
+        Self {
+            tick_id: None,
+            kind: EntryKind::Work,
+            project_name,
+            task_id,
+            task_name,
+            start_time: None,
+            end_time: None,
+            hours: Some(hours),
+            ends_next_day: false,
             notes,
             submitted_at: None,
             updated_at: None,
         }
     }
 
+    /// Creates an already submitted entry for one that was made in Tick
+    /// itself. Tick only knows the hours, so neither does the entry.
+    pub fn from_tick_entry(
+        tick_entry: &TickEntry,
+        project_name: Option<String>,
+        task_name: Option<String>,
+    ) -> Self {
+        Self {
+            tick_id: tick_entry.id,
+            kind: EntryKind::Work,
+            project_name,
+            task_id: Some(tick_entry.task_id),
+            task_name,
+            start_time: None,
+            end_time: None,
+            hours: Some(tick_entry.hours),
+            ends_next_day: false,
+            notes: tick_entry.notes.clone(),
+            submitted_at: Some(Utc::now().naive_utc()),
+            updated_at: None,
+        }
+    }
+
     pub fn get_tick_id(&self) -> Option<&u32> {
         self.tick_id.as_ref()
     }
@@ -117,16 +253,43 @@ impl Entry {
         self.task_name.as_ref()
     }
 
-    pub fn get_start_time(&self) -> &NaiveTime {
-        &self.start_time
+    pub fn get_start_time(&self) -> Option<&NaiveTime> {
+        self.start_time.as_ref()
     }
 
     pub fn get_end_time(&self) -> Option<&NaiveTime> {
         self.end_time.as_ref()
     }
 
+    pub fn get_kind(&self) -> EntryKind {
+        self.kind
+    }
+
+    pub fn set_kind(&mut self, kind: EntryKind) {
+        self.kind = kind;
+    }
+
+    /// Only work booked on a task goes to Tick.
+    pub fn is_submittable(&self) -> bool {
+        self.kind == EntryKind::Work && self.task_id.is_some()
+    }
+
+    /// Work that isn't booked on a task, which is most likely a mistake.
+    pub fn is_unbooked(&self) -> bool {
+        self.kind == EntryKind::Work && self.task_id.is_none()
+    }
+
+    pub fn ends_next_day(&self) -> bool {
+        self.ends_next_day
+    }
+
+    /// Whether the entry was logged as just a number of hours.
+    pub fn has_times(&self) -> bool {
+        self.start_time.is_some()
+    }
+
     pub fn is_missing_end_time(&self) -> bool {
-        self.end_time.is_none()
+        self.has_times() && self.end_time.is_none()
     }
 
     pub fn get_notes(&self) -> &String {
@@ -145,8 +308,28 @@ impl Entry {
         self.project_name = project_name;
         self.task_id = task_id;
         self.task_name = task_name;
-        self.start_time = start_time;
+        self.start_time = Some(start_time);
         self.end_time = end_time;
+        self.hours = None;
+        self.notes = notes;
+        self.updated_at = Some(Utc::now().naive_utc());
+    }
+
+    pub fn update_with_hours(
+        &mut self,
+        project_name: Option<String>,
+        task_id: Option<u32>,
+        task_name: Option<String>,
+        hours: f64,
+        notes: String,
+    ) {
+        self.project_name = project_name;
+        self.task_id = task_id;
+        self.task_name = task_name;
+        self.start_time = None;
+        self.end_time = None;
+        self.ends_next_day = false;
+        self.hours = Some(hours);
         self.notes = notes;
         self.updated_at = Some(Utc::now().naive_utc());
     }
@@ -156,15 +339,66 @@ impl Entry {
     }
 
     pub fn set_end_time(&mut self, end_time: NaiveTime) {
-        self.end_time = Some(end_time)
+        self.end_time = Some(end_time);
+        self.ends_next_day = false;
+    }
+
+    pub fn set_ends_next_day(&mut self, ends_next_day: bool) {
+        self.ends_next_day = ends_next_day;
+    }
+
+    /// Cuts an entry that ends on the next day off at midnight, and returns
+    /// the part after midnight as a new entry for the next day.
+    pub fn split_at_midnight(&mut self) -> Option<Entry> {
+        let end_time = self.end_time?;
+
+        if !self.ends_next_day || end_time == NaiveTime::MIN {
+            return None;
+        }
+
+        self.end_time = Some(NaiveTime::MIN);
+        self.updated_at = Some(Utc::now().naive_utc());
+
+        Some(Entry::create(
+            self.project_name.clone(),
+            self.task_id,
+            self.task_name.clone(),
+            NaiveTime::MIN,
+            Some(end_time),
+            self.notes.clone(),
+        ))
+    }
+
+    /// Time from the start of the day until the end time, which exceeds a
+    /// day for entries that end on the next day.
+    fn end_offset(&self) -> Option<Duration> {
+        let end_offset = self.end_time? - NaiveTime::MIN;
+
+        Some(if self.ends_next_day { end_offset + Duration::days(1) } else { end_offset })
+    }
+
+    /// Whether both entries describe the same work, regardless of whether
+    /// either of them was submitted.
+    pub fn is_same_work(&self, other: &Entry) -> bool {
+        self.task_id == other.task_id
+            && self.start_time == other.start_time
+            && self.end_time == other.end_time
+            && self.hours == other.hours
+            && self.ends_next_day == other.ends_next_day
+            && self.notes == other.notes
     }
 
     pub fn calculate_hours(&self) -> f64 {
-        if self.end_time.is_none() {
-            return 0.0
+        if let Some(hours) = self.hours {
+            return hours;
         }
 
-        let diff = self.end_time.unwrap() - self.start_time;
+        let (start_time, end_offset) = match (self.start_time, self.end_offset()) {
+            (Some(start_time), Some(end_offset)) => (start_time, end_offset),
+            _ => return 0.0,
+        };
+
+        let diff = end_offset - (start_time - NaiveTime::MIN);
 
         (diff.num_minutes() as f64) / 60.0
     }
@@ -192,13 +426,18 @@ impl fmt::Display for Entry {
         write!(
             f,
             "  {}{}{}{}",
-            self.start_time.format("%H:%M"),
-            if self.end_time.is_some() { 
-                format!(" - {}", self.end_time.unwrap().format("%H:%M")) 
-            } else { 
-                String::new() 
+            match self.start_time {
+                Some(start_time) => start_time.format("%H:%M").to_string(),
+                None => format!("{:.2}h", self.calculate_hours()),
+            },
+            if let Some(end_time) = self.end_time {
+                format!(" - {}{}", end_time.format("%H:%M"), if self.ends_next_day { "+1" } else { "" })
+            } else {
+                String::new()
             },
-            if self.task_name.is_some() {
+            if self.kind != EntryKind::Work {
+                format!(": {}", self.kind)
+            } else if self.task_name.is_some() {
                 format!(": {} | {}", self.get_project_name().unwrap(), self.get_task_name().unwrap())
             } else {
                 String::new()
@@ -212,6 +451,160 @@ impl fmt::Display for Entry {
     }
 }
 
+/// Reads a date like `2023-01-30`, `today`, `yesterday`, `fri`, `last fri`,
+/// `-2d` or `5/3`. Weekdays are the latest such day up to `today`, or that
+/// day in the week before with `last`. Month/day dates are in this year.
+pub fn parse_date(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let input = input.trim().to_lowercase();
+
+    match input.as_str() {
+        "today" => return Some(today),
+        "yesterday" => return Some(today - Duration::days(1)),
+        "tomorrow" => return Some(today + Duration::days(1)),
+        _ => {}
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(&input, "%Y-%m-%d") {
+        return Some(date);
+    }
+
+    if let Some(weekday) = input.strip_prefix("last ") {
+        let weekday: Weekday = weekday.trim().parse().ok()?;
+        let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64 + 7);
+        return Some(monday + Duration::days(weekday.num_days_from_monday() as i64));
+    }
+
+    if let Ok(weekday) = input.parse::<Weekday>() {
+        let days_back = (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+        return Some(today - Duration::days(days_back as i64));
+    }
+
+    if let Some(offset) = input.strip_suffix('d') {
+        if offset.starts_with(['+', '-']) {
+            return Some(today + Duration::days(offset.parse().ok()?));
+        }
+    }
+
+    if let Some((month, day)) = input.split_once('/') {
+        if let Ok(date) = NaiveDate::parse_from_str(&input, "%m/%d/%Y") {
+            return Some(date);
+        }
+        return NaiveDate::from_ymd_opt(today.year(), month.parse().ok()?, day.parse().ok()?);
+    }
+
+    None
+}
+
+/// Reads a duration like `15m`, `1h`, `1h30m` or `1.5h`.
+pub fn parse_duration(input: &str) -> Option<Duration> {
+    let input = input.trim().to_lowercase();
+
+    let (hours, minutes) = match input.split_once('h') {
+        Some((hours, minutes)) => (hours, minutes),
+        None => ("", input.as_str()),
+    };
+    let hours: f64 = if hours.is_empty() { 0.0 } else { hours.parse().ok()? };
+    let minutes = minutes.trim_end_matches('m');
+    let minutes: i64 = if minutes.is_empty() { 0 } else { minutes.parse().ok()? };
+
+    // Way too long durations, like inf hours, would overflow.
+    let total = ((hours * 60.0).round() as i64).checked_add(minutes)?;
+    (total > 0 && total < i64::MAX / 60_000).then(|| Duration::minutes(total))
+}
+
+/// Reads a number of hours like `2.5`, or a duration like `2h30m`, of more
+/// than nothing and at most a day.
+pub fn parse_hours(input: &str) -> Option<f64> {
+    let input = input.trim();
+
+    let hours = match input.parse::<f64>() {
+        Ok(hours) => hours,
+        Err(_) => parse_duration(input)?.num_minutes() as f64 / 60.0,
+    };
+
+    (hours.is_finite() && hours > 0.0 && hours <= 24.0).then_some(hours)
+}
+
+/// Reads a time like `9:30`, `930`, `9`, `9.30`, `9:30pm`, `now`, `+15m`,
+/// `-1h`, `prev` or `prev+15m`. Relative times count from `now`, or from
+/// `previous`, the end of the previous entry, when they start with `prev`.
+pub fn parse_time(input: &str, now: NaiveTime, previous: Option<NaiveTime>) -> Option<NaiveTime> {
+    let input = input.trim().to_lowercase();
+
+    if input == "now" {
+        return Some(now);
+    }
+
+    if let Some(offset) = input.strip_prefix("prev") {
+        let previous = previous?;
+        return if offset.is_empty() { Some(previous) } else { offset_time(previous, offset) };
+    }
+
+    if input.starts_with(['+', '-']) {
+        return offset_time(now, &input);
+    }
+
+    parse_clock_time(&input)
+}
+
+/// Reads an end time like `parse_time`, which may also lie on the next day
+/// when followed by `+1`, like `01:30+1`.
+pub fn parse_end_time(input: &str, now: NaiveTime, previous: Option<NaiveTime>) -> Option<(NaiveTime, bool)> {
+    let input = input.trim().to_lowercase();
+
+    if let Some(time) = input.strip_suffix("+1").and_then(|time| parse_clock_time(time.trim())) {
+        return Some((time, true));
+    }
+
+    parse_time(&input, now, previous).map(|time| (time, false))
+}
+
+fn offset_time(time: NaiveTime, offset: &str) -> Option<NaiveTime> {
+    let offset = offset.trim();
+    let duration = match offset.strip_prefix('-') {
+        Some(duration) => -parse_duration(duration)?,
+        None => parse_duration(offset.strip_prefix('+')?)?,
+    };
+
+    // Relative times stay within the same day.
+    let (time, overflow) = time.overflowing_add_signed(duration);
+    (overflow == 0).then_some(time)
+}
+
+fn parse_clock_time(input: &str) -> Option<NaiveTime> {
+    let (input, meridiem) = if let Some(input) = input.strip_suffix("am") {
+        (input.trim(), Some(0))
+    } else if let Some(input) = input.strip_suffix("pm") {
+        (input.trim(), Some(12))
+    } else {
+        (input, None)
+    };
+
+    let (hours, minutes) = match input.split_once([':', '.']) {
+        Some((hours, minutes)) if minutes.len() == 2 => (hours, minutes),
+        Some(_) => return None,
+        None if input.len() <= 2 => (input, "0"),
+        None if input.len() <= 4 => input.split_at(input.len() - 2),
+        None => return None,
+    };
+
+    if !hours.chars().chain(minutes.chars()).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let mut hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+
+    if let Some(offset) = meridiem {
+        if hours == 0 || hours > 12 {
+            return None;
+        }
+        hours = hours % 12 + offset;
+    }
+
+    NaiveTime::from_hms_opt(hours, minutes, 0)
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct EntryList {
     entries: Vec<Entry>,
@@ -239,31 +632,45 @@ impl EntryList {
         &self.entries
     }
 
-    pub fn get_last(&self) -> Option<&Entry> {
-        self.entries.last()
+    /// The end time of the entry right before `index`, which is where an
+    /// entry at that position would usually start.
+    pub fn get_end_time_before(&self, index: usize) -> Option<NaiveTime> {
+        self.entries[..index.min(self.entries.len())]
+            .iter()
+            .rev()
+            .find(|entry| entry.has_times())
+            .and_then(|entry| entry.end_time)
+    }
+
+    /// Work entries without a task, which won't be submitted.
+    pub fn get_unbooked(&self) -> Vec<&Entry> {
+        self.entries.iter().filter(|entry| entry.is_unbooked()).collect()
     }
 
-    pub fn get_last_mut(&mut self) -> Option<&mut Entry> {
-        self.entries.last_mut()
+    /// Entries of just a number of hours, which are listed separately.
+    pub fn get_untimed(&self) -> Vec<&Entry> {
+        self.entries.iter().filter(|entry| !entry.has_times()).collect()
     }
 
     pub fn add(&mut self, entry: Entry) {
         self.entries.push(entry);
+        self.all_submitted = false;
 
         self.sort();
     }
 
+    /// Sorts on start time, with the entries of just hours at the end.
     pub fn sort(&mut self) {
-        self.entries.sort_by(|a, b| a.start_time.cmp(&b.start_time));
+        self.entries.sort_by_key(|e| (e.start_time.is_none(), e.start_time));
     }
 
+    /// Lets entries without an end time run until the next one starts.
     pub fn set_end_times(&mut self) {
-        let mut iter = self.entries.iter_mut();
+        let mut iter = self.entries.iter_mut().filter(|entry| entry.has_times());
 
         if let Some(mut current) = iter.next() {
             for next in iter {
-                if current.end_time.is_none() {
-                    let end_time = next.start_time.clone();
+                if let (None, Some(end_time)) = (current.end_time, next.start_time) {
                     current.set_end_time(end_time);
                 }
                 current = next;
@@ -275,6 +682,60 @@ impl EntryList {
         self.entries.remove(index);
     }
 
+    /// Finds entries that end before they start, entries that overlap and
+    /// unbooked time between entries.
+    pub fn validate(&self) -> Vec<EntryProblem> {
+        let mut problems = Vec::new();
+        // The start time and end offset of the entry that runs the longest.
+        let mut latest: Option<(NaiveTime, Duration)> = None;
+        // Entries without an end time run until the next one starts.
+        let mut open_ended = false;
+
+        for entry in &self.entries {
+            let start_time = match entry.start_time {
+                Some(start_time) => start_time,
+                None => continue,
+            };
+            let start_offset = start_time - NaiveTime::MIN;
+            let end_offset = match entry.end_offset() {
+                Some(end_offset) if end_offset < start_offset => {
+                    problems.push(EntryProblem::Inverted {
+                        start: start_time,
+                        end: entry.end_time.unwrap(),
+                    });
+                    continue;
+                }
+                end_offset => end_offset,
+            };
+
+            if let Some((latest_start, latest_end)) = latest {
+                if start_offset < latest_end {
+                    problems.push(EntryProblem::Overlap {
+                        first: latest_start,
+                        second: start_time,
+                        start: start_time,
+                        end: NaiveTime::MIN + end_offset.map_or(latest_end, |end| end.min(latest_end)),
+                    });
+                } else if start_offset > latest_end && !open_ended {
+                    problems.push(EntryProblem::Gap {
+                        start: NaiveTime::MIN + latest_end,
+                        end: start_time,
+                    });
+                }
+            }
+
+            open_ended = end_offset.is_none();
+
+            if let Some(end_offset) = end_offset {
+                if latest.is_none_or(|(_, latest_end)| end_offset > latest_end) {
+                    latest = Some((start_time, end_offset));
+                }
+            }
+        }
+
+        problems
+    }
+
     pub fn all_submitted(&self) -> bool {
         self.all_submitted
     }
@@ -286,13 +747,370 @@ impl EntryList {
 
 impl fmt::Display for EntryList {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for entry in &self.entries {
-            write!(f, "{}\n", entry)?;
+        for entry in self.entries.iter().filter(|entry| entry.has_times()) {
+            writeln!(f, "{}", entry)?;
         }
+
+        let untimed = self.get_untimed();
+        if !untimed.is_empty() {
+            writeln!(f, "Without times:")?;
+            for entry in untimed {
+                writeln!(f, "{}", entry)?;
+            }
+        }
+
         Ok(())
     }
 }
 
+#[derive(Debug, PartialEq)]
+pub enum EntryProblem {
+    /// An entry ends before it starts.
+    Inverted { start: NaiveTime, end: NaiveTime },
+    /// The entries starting at `first` and `second` overlap from `start` to `end`.
+    Overlap { first: NaiveTime, second: NaiveTime, start: NaiveTime, end: NaiveTime },
+    /// Nothing is booked from `start` to `end`.
+    Gap { start: NaiveTime, end: NaiveTime },
+}
+
+impl EntryProblem {
+    /// Gaps are fine to submit, the other problems would book wrong hours.
+    pub fn is_blocking(&self) -> bool {
+        !matches!(self, EntryProblem::Gap { .. })
+    }
+}
+
+impl fmt::Display for EntryProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EntryProblem::Inverted { start, end } => write!(
+                f,
+                "The entry at {} ends before it starts, at {}",
+                start.format("%H:%M"),
+                end.format("%H:%M"),
+            ),
+            EntryProblem::Overlap { first, second, start, end } => write!(
+                f,
+                "The entries at {} and {} overlap from {} to {}",
+                first.format("%H:%M"),
+                second.format("%H:%M"),
+                start.format("%H:%M"),
+                end.format("%H:%M"),
+            ),
+            EntryProblem::Gap { start, end } => write!(
+                f,
+                "Nothing is booked from {} to {}",
+                start.format("%H:%M"),
+                end.format("%H:%M"),
+            ),
+        }
+    }
+}
+
+/// A named project, task and notes combination for frequent entries.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Template {
+    name: String,
+    project_name: Option<String>,
+    task_id: Option<u32>,
+    task_name: Option<String>,
+    #[serde(default)]
+    notes: String,
+    duration_minutes: Option<i64>,
+}
+
+impl Template {
+    pub fn create(
+        name: String,
+        project_name: Option<String>,
+        task_id: Option<u32>,
+        task_name: Option<String>,
+        notes: String,
+        duration_minutes: Option<i64>,
+    ) -> Self {
+        Self {
+            name,
+            project_name,
+            task_id,
+            task_name,
+            notes,
+            duration_minutes,
+        }
+    }
+
+    pub fn get_name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn get_project_name(&self) -> Option<&String> {
+        self.project_name.as_ref()
+    }
+
+    pub fn get_task_id(&self) -> Option<&u32> {
+        self.task_id.as_ref()
+    }
+
+    pub fn get_task_name(&self) -> Option<&String> {
+        self.task_name.as_ref()
+    }
+
+    pub fn get_notes(&self) -> &String {
+        &self.notes
+    }
+
+    /// The end time of an entry starting at `start_time`, when the
+    /// template has a duration.
+    pub fn get_end_time(&self, start_time: &NaiveTime) -> Option<NaiveTime> {
+        self.duration_minutes
+            .map(|minutes| *start_time + Duration::minutes(minutes))
+    }
+}
+
+impl fmt::Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}{}",
+            self.name,
+            if let (Some(project_name), Some(task_name)) = (&self.project_name, &self.task_name) {
+                format!(": {} | {}", project_name, task_name)
+            } else {
+                String::new()
+            },
+            if !self.notes.is_empty() {
+                format!(": {}", self.notes)
+            } else {
+                String::new()
+            },
+            if let Some(minutes) = self.duration_minutes {
+                format!(" ({}m)", minutes)
+            } else {
+                String::new()
+            },
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Timer {
+    #[serde(default)]
+    kind: EntryKind,
+    project_name: Option<String>,
+    task_id: Option<u32>,
+    task_name: Option<String>,
+    notes: String,
+    started_at: NaiveDateTime,
+}
+
+impl Timer {
+    pub fn start(
+        project_name: Option<String>,
+        task_id: Option<u32>,
+        task_name: Option<String>,
+        notes: String,
+        started_at: NaiveDateTime,
+    ) -> Self {
+        Self {
+            kind: EntryKind::Work,
+            project_name,
+            task_id,
+            task_name,
+            notes,
+            started_at,
+        }
+    }
+
+    pub fn set_kind(&mut self, kind: EntryKind) {
+        self.kind = kind;
+    }
+
+    pub fn get_started_at(&self) -> &NaiveDateTime {
+        &self.started_at
+    }
+
+    /// Turns the timer into a completed entry for the day it was started on.
+    /// Entries end on the next day at the latest, so a timer stopped later
+    /// than that ends at midnight.
+    pub fn stop(&self, stopped_at: NaiveDateTime) -> Entry {
+        let days = (stopped_at.date() - self.started_at.date()).num_days();
+        let end_time = if days > 1 { NaiveTime::MIN } else { stopped_at.time() };
+
+        let mut entry = Entry::create(
+            self.project_name.clone(),
+            self.task_id,
+            self.task_name.clone(),
+            self.started_at.time(),
+            Some(end_time),
+            self.notes.clone(),
+        );
+        entry.set_ends_next_day(days > 0);
+        entry.set_kind(self.kind);
+
+        entry
+    }
+}
+
+impl fmt::Display for Timer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "  {} since {}{}{}",
+            self.started_at.format("%Y-%m-%d"),
+            self.started_at.format("%H:%M"),
+            if self.kind != EntryKind::Work {
+                format!(": {}", self.kind)
+            } else if let (Some(project_name), Some(task_name)) = (&self.project_name, &self.task_name) {
+                format!(": {} | {}", project_name, task_name)
+            } else {
+                String::new()
+            },
+            if !self.notes.is_empty() {
+                format!(": {}", self.notes)
+            } else {
+                String::new()
+            }
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundingMode {
+    Nearest,
+    Up,
+    Down,
+}
+
+/// Rounds hours to a number of minutes, like up to 15 minutes.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+pub struct Rounding {
+    mode: RoundingMode,
+    minutes: u32,
+}
+
+impl FromStr for RoundingMode {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_lowercase().as_str() {
+            "nearest" => Ok(RoundingMode::Nearest),
+            "up" => Ok(RoundingMode::Up),
+            "down" => Ok(RoundingMode::Down),
+            _ => Err("Unknown rounding. Please provide nearest, up or down".to_string()),
+        }
+    }
+}
+
+impl Rounding {
+    pub fn create(mode: RoundingMode, minutes: u32) -> Self {
+        Self { mode, minutes }
+    }
+
+    /// Rounds to whole steps, but never below one step for any time worked,
+    /// so short entries aren't submitted as 0 hours.
+    pub fn round(&self, hours: f64) -> f64 {
+        if self.minutes == 0 {
+            return hours;
+        }
+
+        // Entries are whole minutes, so get rid of floating point noise first.
+        let steps = (hours * 60.0).round() / self.minutes as f64;
+        let steps = match self.mode {
+            RoundingMode::Nearest => steps.round(),
+            RoundingMode::Up => steps.ceil(),
+            RoundingMode::Down => steps.floor(),
+        };
+        let steps = if steps > 0.0 || hours <= 0.0 { steps } else { 1.0 };
+
+        steps * self.minutes as f64 / 60.0
+    }
+}
+
+impl fmt::Display for Rounding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.mode {
+            RoundingMode::Nearest => write!(f, "to the nearest {} minutes", self.minutes),
+            RoundingMode::Up => write!(f, "up to {} minutes", self.minutes),
+            RoundingMode::Down => write!(f, "down to {} minutes", self.minutes),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct ProjectRounding {
+    project: String,
+    mode: RoundingMode,
+    minutes: u32,
+}
+
+/// The rounding for all projects, and the ones for specific projects that
+/// take precedence over it.
+#[derive(Serialize, Deserialize, Default, PartialEq, Debug, Clone)]
+pub struct RoundingRules {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    projects: Vec<ProjectRounding>,
+    #[serde(default)]
+    default: Option<Rounding>,
+}
+
+impl RoundingRules {
+    pub fn create(default: Option<Rounding>) -> Self {
+        Self { projects: vec![], default }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.default.is_none() && self.projects.is_empty()
+    }
+
+    pub fn get_default(&self) -> Option<&Rounding> {
+        self.default.as_ref()
+    }
+
+    pub fn set_default(&mut self, rounding: Option<Rounding>) {
+        self.default = rounding;
+    }
+
+    /// The projects with their own rounding, by name.
+    pub fn get_projects(&self) -> Vec<(&String, Rounding)> {
+        self.projects
+            .iter()
+            .map(|p| (&p.project, Rounding::create(p.mode, p.minutes)))
+            .collect()
+    }
+
+    pub fn set_project(&mut self, project: String, rounding: Rounding) {
+        self.remove_project(&project);
+        self.projects.push(ProjectRounding { project, mode: rounding.mode, minutes: rounding.minutes });
+        self.projects.sort_by(|a, b| a.project.cmp(&b.project));
+    }
+
+    pub fn remove_project(&mut self, project: &str) -> bool {
+        let count = self.projects.len();
+        self.projects.retain(|p| p.project != project);
+
+        self.projects.len() < count
+    }
+
+    pub fn for_entry(&self, entry: &Entry) -> Option<Rounding> {
+        entry
+            .project_name
+            .as_ref()
+            .and_then(|name| self.projects.iter().find(|p| &p.project == name))
+            .map(|p| Rounding::create(p.mode, p.minutes))
+            .or(self.default)
+    }
+
+    /// The hours of an entry as they're submitted.
+    pub fn round_hours(&self, entry: &Entry) -> f64 {
+        let hours = entry.calculate_hours();
+
+        match self.for_entry(entry) {
+            Some(rounding) => rounding.round(hours),
+            None => hours,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TickEntry {
     #[serde(skip_serializing)]
@@ -305,13 +1123,13 @@ pub struct TickEntry {
 }
 
 impl TickEntry {
-    pub fn from_entry(date: String, entry: Entry) -> Self {
+    pub fn from_entry(date: String, entry: Entry, rounding: &RoundingRules) -> Self {
         Self {
             entry: Some(entry.clone()),
             id: entry.get_tick_id().copied(),
             date,
             task_id: *entry.get_task_id().unwrap(),
-            hours: entry.calculate_hours(),
+            hours: rounding.round_hours(&entry),
             notes: entry.get_notes().clone(),
         }
     }
@@ -339,6 +1157,14 @@ impl TickEntry {
     pub fn get_notes(&self) -> &String {
         &self.notes
     }
+
+    /// Whether the submitted version of an entry no longer matches this one,
+    /// ignoring rounding differences in the hours.
+    pub fn differs_from(&self, entry: &Entry, rounding: &RoundingRules) -> bool {
+        entry.get_task_id() != Some(&self.task_id)
+            || (rounding.round_hours(entry) - self.hours).abs() >= 0.01
+            || entry.get_notes() != &self.notes
+    }
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -347,12 +1173,12 @@ pub struct TickEntryList {
 }
 
 impl TickEntryList {
-    pub fn from_entry_list(date: &String, entry_list: &EntryList) -> Self {
+    pub fn from_entry_list(date: &str, entry_list: &EntryList, rounding: &RoundingRules) -> Self {
         let tick_entries = entry_list
         .get_all()
         .iter()
-        .filter(|entry| entry.get_task_id().is_some())
-        .map(|entry| TickEntry::from_entry(date.clone(), entry.to_owned()))
+        .filter(|entry| entry.is_submittable())
+        .map(|entry| TickEntry::from_entry(date.to_string(), entry.to_owned(), rounding))
         .collect::<Vec<TickEntry>>();
 
         Self { tick_entries }
@@ -370,12 +1196,14 @@ impl TickEntryList {
     pub fn len(&self) -> usize {
         self.tick_entries.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.tick_entries.is_empty()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::str::FromStr;
-
     use super::*;
 
     #[test]
@@ -425,7 +1253,7 @@ mod tests {
             "notes".to_string(),
         ));
 
-        assert_eq!(NaiveTime::from_str("09:00:00").unwrap(), entries.get_all()[0].start_time);
+        assert_eq!(NaiveTime::from_str("09:00:00").ok(), entries.get_all()[0].start_time);
 
         entries.add(Entry::create(
             Some("project C".to_string()),
@@ -436,7 +1264,7 @@ mod tests {
             "notes".to_string(),
         ));
 
-        assert_eq!(NaiveTime::from_str("08:59:59").unwrap(), entries.get_all()[0].start_time);
+        assert_eq!(NaiveTime::from_str("08:59:59").ok(), entries.get_all()[0].start_time);
     }
 
     #[test]
@@ -514,4 +1342,256 @@ mod tests {
         );
         assert_eq!(None, entries.get(2).end_time);
     }
+
+    #[test]
+    fn test_find_by_name() {
+        let names = vec![
+            "Acme".to_string(),
+            "Acme Support".to_string(),
+            "Globex".to_string(),
+        ];
+
+        assert_eq!(Ok(0), find_by_name(&names, "Acme"));
+        assert_eq!(Ok(2), find_by_name(&names, "globex"));
+        assert_eq!(Ok(1), find_by_name(&names, "supp"));
+        assert_eq!(Err(NameMatchError::NotFound), find_by_name(&names, "Initech"));
+        assert_eq!(
+            Err(NameMatchError::Ambiguous(vec!["Acme".to_string(), "Acme Support".to_string()])),
+            find_by_name(&names, "acm"),
+        );
+    }
+
+    #[test]
+    fn test_add_resets_all_submitted() {
+        let mut entries = EntryList::empty();
+        entries.set_all_submitted(true);
+
+        entries.add(Entry::create(
+            None,
+            None,
+            None,
+            NaiveTime::from_str("09:00:00").unwrap(),
+            None,
+            String::new(),
+        ));
+
+        assert!(!entries.all_submitted());
+    }
+
+    #[test]
+    fn test_stop_timer() {
+        let timer = Timer::start(
+            Some("project A".to_string()),
+            Some(1),
+            Some("task 1".to_string()),
+            "notes".to_string(),
+            NaiveDateTime::from_str("2023-05-01T09:00:00").unwrap(),
+        );
+
+        let entry = timer.stop(NaiveDateTime::from_str("2023-05-01T10:30:00").unwrap());
+        assert_eq!(Some(&NaiveTime::from_str("09:00:00").unwrap()), entry.get_start_time());
+        assert_eq!(Some(&NaiveTime::from_str("10:30:00").unwrap()), entry.get_end_time());
+        assert_eq!(1.5, entry.calculate_hours());
+
+        let entry = timer.stop(NaiveDateTime::from_str("2023-05-02T01:00:00").unwrap());
+        assert_eq!(Some(&NaiveTime::from_str("01:00:00").unwrap()), entry.get_end_time());
+        assert!(entry.ends_next_day());
+        assert_eq!(16.0, entry.calculate_hours());
+
+        let entry = timer.stop(NaiveDateTime::from_str("2023-05-03T01:00:00").unwrap());
+        assert_eq!(Some(&NaiveTime::MIN), entry.get_end_time());
+        assert_eq!(15.0, entry.calculate_hours());
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let now = NaiveTime::from_hms_opt(14, 5, 0).unwrap();
+        let previous = NaiveTime::from_hms_opt(12, 30, 0).unwrap();
+        let parse = |input| parse_time(input, now, Some(previous)).map(|t| t.format("%H:%M").to_string());
+
+        assert_eq!(Some("09:30".to_string()), parse("9:30"));
+        assert_eq!(Some("09:30".to_string()), parse("930"));
+        assert_eq!(Some("09:30".to_string()), parse("9.30"));
+        assert_eq!(Some("17:45".to_string()), parse("1745"));
+        assert_eq!(Some("09:00".to_string()), parse("9"));
+        assert_eq!(Some("21:30".to_string()), parse("9:30pm"));
+        assert_eq!(Some("00:15".to_string()), parse("12:15am"));
+        assert_eq!(Some("12:00".to_string()), parse("12pm"));
+        assert_eq!(Some("14:05".to_string()), parse("now"));
+        assert_eq!(Some("14:20".to_string()), parse("+15m"));
+        assert_eq!(Some("13:05".to_string()), parse("-1h"));
+        assert_eq!(Some("12:30".to_string()), parse("prev"));
+        assert_eq!(Some("12:45".to_string()), parse("prev+15m"));
+
+        assert_eq!(None, parse("25:00"));
+        assert_eq!(None, parse("9:3"));
+        assert_eq!(None, parse("13pm"));
+        assert_eq!(None, parse("+12h"));
+        assert_eq!(None, parse("soon"));
+        assert_eq!(None, parse_time("prev", now, None));
+    }
+
+    #[test]
+    fn test_parse_date() {
+        // A Wednesday
+        let today = NaiveDate::from_ymd_opt(2024, 5, 8).unwrap();
+        let parse = |input| parse_date(input, today).map(|d| d.format("%Y-%m-%d").to_string());
+
+        assert_eq!(Some("2024-01-30".to_string()), parse("2024-01-30"));
+        assert_eq!(Some("2024-05-08".to_string()), parse("today"));
+        assert_eq!(Some("2024-05-07".to_string()), parse("yesterday"));
+        assert_eq!(Some("2024-05-06".to_string()), parse("mon"));
+        assert_eq!(Some("2024-05-08".to_string()), parse("Wednesday"));
+        assert_eq!(Some("2024-05-03".to_string()), parse("fri"));
+        assert_eq!(Some("2024-05-03".to_string()), parse("last fri"));
+        assert_eq!(Some("2024-04-29".to_string()), parse("last mon"));
+        assert_eq!(Some("2024-05-06".to_string()), parse("-2d"));
+        assert_eq!(Some("2024-05-03".to_string()), parse("5/3"));
+        assert_eq!(Some("2023-12-24".to_string()), parse("12/24/2023"));
+
+        assert_eq!(None, parse("13/1"));
+        assert_eq!(None, parse("someday"));
+        assert_eq!(None, parse("2d"));
+    }
+
+    #[test]
+    fn test_validate_entries() {
+        let time = |input| NaiveTime::from_str(input).unwrap();
+        let entry = |start, end| Entry::create(None, None, None, time(start), Some(time(end)), String::new());
+
+        let mut entries = EntryList::empty();
+        entries.add(entry("09:00:00", "10:30:00"));
+        entries.add(entry("10:00:00", "11:00:00"));
+        entries.add(entry("12:00:00", "11:30:00"));
+        entries.add(entry("13:00:00", "14:00:00"));
+
+        assert_eq!(
+            vec![
+                EntryProblem::Overlap {
+                    first: time("09:00:00"),
+                    second: time("10:00:00"),
+                    start: time("10:00:00"),
+                    end: time("10:30:00"),
+                },
+                EntryProblem::Inverted { start: time("12:00:00"), end: time("11:30:00") },
+                EntryProblem::Gap { start: time("11:00:00"), end: time("13:00:00") },
+            ],
+            entries.validate(),
+        );
+
+        let mut entries = EntryList::empty();
+        entries.add(entry("09:00:00", "10:00:00"));
+        entries.add(entry("10:00:00", "11:00:00"));
+        entries.add(Entry::create(None, None, None, time("11:00:00"), None, String::new()));
+        entries.add(entry("12:00:00", "13:00:00"));
+
+        assert!(entries.validate().is_empty());
+    }
+
+    #[test]
+    fn test_entry_crossing_midnight() {
+        let now = NaiveTime::from_hms_opt(14, 5, 0).unwrap();
+        let (end_time, ends_next_day) = parse_end_time("01:30+1", now, None).unwrap();
+        assert_eq!(NaiveTime::from_str("01:30:00").unwrap(), end_time);
+        assert!(ends_next_day);
+        assert_eq!(Some((now, false)), parse_end_time("now", now, None));
+
+        let mut entry = Entry::create(
+            None,
+            None,
+            None,
+            NaiveTime::from_str("22:00:00").unwrap(),
+            Some(end_time),
+            String::new(),
+        );
+        entry.set_ends_next_day(ends_next_day);
+        assert_eq!(3.5, entry.calculate_hours());
+
+        let mut entries = EntryList::empty();
+        entries.add(entry.clone());
+        assert!(entries.validate().is_empty());
+
+        let next_day = entry.split_at_midnight().unwrap();
+        assert_eq!(2.0, entry.calculate_hours());
+        assert_eq!(Some(&NaiveTime::MIN), next_day.get_start_time());
+        assert_eq!(1.5, next_day.calculate_hours());
+        assert_eq!(None, entry.split_at_midnight());
+    }
+
+    #[test]
+    fn test_only_work_on_tasks_is_submitted() {
+        let time = |input| NaiveTime::from_str(input).unwrap();
+        let mut entries = EntryList::empty();
+
+        entries.add(Entry::create(
+            Some("Acme".to_string()),
+            Some(1),
+            Some("Development".to_string()),
+            time("09:00:00"),
+            Some(time("12:00:00")),
+            String::new(),
+        ));
+        let mut lunch = Entry::create(None, None, None, time("12:00:00"), Some(time("12:30:00")), String::new());
+        lunch.set_kind(EntryKind::Break);
+        entries.add(lunch);
+        entries.add(Entry::create(None, None, None, time("12:30:00"), Some(time("13:00:00")), String::new()));
+
+        assert_eq!("  12:00 - 12:30: Break", entries.get(1).to_string());
+        assert_eq!(vec![entries.get(2)], entries.get_unbooked());
+        assert_eq!(1, TickEntryList::from_entry_list("2024-05-03", &entries, &RoundingRules::default()).len());
+    }
+
+    #[test]
+    fn test_round_hours() {
+        let nearest = Rounding::create(RoundingMode::Nearest, 15);
+        assert_eq!(0.25, nearest.round(17.0 / 60.0));
+        assert_eq!(0.5, nearest.round(23.0 / 60.0));
+        assert_eq!(0.5, Rounding::create(RoundingMode::Up, 30).round(1.0 / 60.0));
+        assert_eq!(0.5, Rounding::create(RoundingMode::Down, 30).round(29.0 / 60.0));
+        assert_eq!(0.25, nearest.round(5.0 / 60.0));
+        assert_eq!(0.0, nearest.round(0.0));
+        assert_eq!(0.3, Rounding::create(RoundingMode::Up, 6).round(17.0 / 60.0));
+
+        let mut rules = RoundingRules::create(Some(nearest));
+        rules.set_project("Acme".to_string(), Rounding::create(RoundingMode::Up, 30));
+
+        let time = |input| NaiveTime::from_str(input).unwrap();
+        let mut entry = Entry::create(None, Some(1), None, time("09:00:00"), Some(time("09:17:00")), String::new());
+        assert_eq!(0.25, rules.round_hours(&entry));
+
+        entry.update(Some("Acme".to_string()), Some(1), None, time("09:00:00"), Some(time("09:17:00")), String::new());
+        assert_eq!(0.5, rules.round_hours(&entry));
+    }
+
+    #[test]
+    fn test_entries_with_just_hours() {
+        let time = |input| NaiveTime::from_str(input).unwrap();
+        let mut entries = EntryList::empty();
+
+        entries.add(Entry::create_with_hours(None, Some(1), None, 2.5, "Code review".to_string()));
+        entries.add(Entry::create(None, Some(1), None, time("09:00:00"), None, String::new()));
+        entries.add(Entry::create(None, Some(1), None, time("10:00:00"), Some(time("11:00:00")), String::new()));
+
+        assert_eq!(Some(time("11:00:00")), entries.get_end_time_before(3));
+        entries.set_end_times();
+
+        assert_eq!(Some(time("10:00:00")), entries.get(0).end_time);
+        assert!(!entries.get(2).is_missing_end_time());
+        assert_eq!(2.5, entries.get(2).calculate_hours());
+        assert!(entries.validate().is_empty());
+        assert_eq!(
+            "  09:00 - 10:00\n  10:00 - 11:00\nWithout times:\n  2.50h: Code review\n",
+            entries.to_string(),
+        );
+
+        assert_eq!(Some(2.5), parse_hours("2.5"));
+        assert_eq!(Some(1.5), parse_hours("1h30m"));
+        assert_eq!(None, parse_hours("0"));
+        assert_eq!(Some(24.0), parse_hours("24"));
+        assert_eq!(None, parse_hours("inf"));
+        assert_eq!(None, parse_hours("NaN"));
+        assert_eq!(None, parse_hours("1e300"));
+        assert_eq!(None, parse_hours("25h"));
+        assert_eq!(None, parse_hours("infh5m"));
+    }
 }
@@ -1,8 +1,7 @@
-use std::{process, error::Error};
-use args::{Command::*, Args};
-use config::Config;
-
-type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
+use std::process;
+use args::{Command::*, AccountCommand, Args, AddArgs, CheckArgs, ExportArgs, ImportArgs, LoginArgs, ProfileCommand, PullArgs, ReportArgs, RoundingCommand, SubmitArgs, TemplateCommand, TimerArgs};
+use config::{Config, Profiles};
+use error::{TickError, TickResult};
 
 mod commands;
 mod config;
@@ -13,69 +12,198 @@ mod ui;
 mod cache;
 mod http;
 mod repository;
+mod error;
 
 fn main() {
-    if let Err(e) = args::get().and_then(try_main) {
+    let result = match args::get() {
+        Ok(args) => try_main(args),
+        Err(e) => Err(TickError::Other(e.to_string())),
+    };
+
+    if let Err(e) = result {
         eprintln!("{}", e);
-        process::exit(1);
+        process::exit(e.exit_code());
     }
 }
 
-fn try_main(args: Args) -> Result<()> {
-    let mut config = config::load()?;
+fn try_main(args: Args) -> TickResult<()> {
+    let profiles = config::load_profiles()?;
+
+    // Managing profiles doesn't use one, so a profile that's given but
+    // doesn't exist yet can still be added.
+    let profile = match args.command {
+        Profile { .. } => None,
+        _ => resolve_profile(&profiles, args.profile.as_deref())?,
+    };
 
-    let matched = match args.command {
-        Login => login(&mut config),
+    files::set_profile(profile.as_deref());
+    let mut config = config::load(profile.as_deref())?;
+
+    match args.command {
+        Login(args) => login(&mut config, &args),
         Logout => logout(&config),
-        Add => add(&config),
+        Account { command } => match command {
+            AccountCommand::Switch => switch_account(&mut config),
+        },
+        Add(args) => add(&config, &args),
         List => list(&config),
         Edit => edit(&config),
-        Submit => submit(&config),
+        Submit(args) => submit(&config, &args),
         Delete => delete(&config),
-    }?;
+        Check(args) => check(&config, &args),
+        Start(args) => start(&config, &args),
+        Stop => stop(&config),
+        Switch(args) => switch(&config, &args),
+        Pull(args) => pull(&config, &args),
+        Report(args) => report(&config, &args),
+        Export(args) => export(&config, &args),
+        Import(args) => import(&config, &args),
+        Rounding { command } => manage_rounding(&mut config, &command),
+        Template { command } => manage_templates(&mut config, &command),
+        Profile { command } => {
+            manage_profiles(Some(args.profile.as_deref().unwrap_or(profiles.get_default())), &command)
+        }
+    }
+}
 
-    if matched {
-        process::exit(0)
+/// Picks the given profile or else the configured default one, where `None`
+/// stands for the default profile.
+fn resolve_profile(profiles: &Profiles, profile: Option<&str>) -> TickResult<Option<String>> {
+    let profile = profile.unwrap_or(profiles.get_default());
+
+    if !profiles.contains(profile) {
+        return Err(TickError::Other(format!(
+            "Unknown profile \"{}\". Run `tick profile add {}` to create it.",
+            profile, profile,
+        )));
+    }
+
+    if profile == config::DEFAULT_PROFILE {
+        Ok(None)
     } else {
-        process::exit(1)
+        Ok(Some(profile.to_string()))
     }
 }
 
-fn login(config: &mut Config) -> Result<bool> {
-    Ok(commands::login(config).is_ok())
+fn login(config: &mut Config, args: &LoginArgs) -> TickResult<()> {
+    commands::login(config, args)
 }
 
-fn logout(config: &Config) -> Result<bool> {
-    Ok(commands::logout(config).is_ok())
+fn logout(config: &Config) -> TickResult<()> {
+    commands::logout(config)
+}
+
+fn switch_account(config: &mut Config) -> TickResult<()> {
+    commands::check_auth(config)?;
+
+    commands::switch_account(config)
 }
 
-fn add(config: &Config) -> Result<bool> {
-    commands::check_auth(config);
+fn add(config: &Config, args: &AddArgs) -> TickResult<()> {
+    commands::check_auth(config)?;
 
-    Ok(commands::add_entry(config).is_ok())
+    commands::add_entry(config, args)
 }
 
-fn list(config: &Config) -> Result<bool> {
-    commands::check_auth(config);
+fn list(config: &Config) -> TickResult<()> {
+    commands::check_auth(config)?;
 
-    Ok(commands::list_entries().is_ok())
+    commands::list_entries()
 }
 
-fn edit(config: &Config) -> Result<bool> {
-    commands::check_auth(config);
+fn edit(config: &Config) -> TickResult<()> {
+    commands::check_auth(config)?;
 
-    Ok(commands::edit_entry(config).is_ok())
+    commands::edit_entry(config)
 }
 
-fn submit(config: &Config) -> Result<bool> {
-    commands::check_auth(config);
+fn submit(config: &Config, args: &SubmitArgs) -> TickResult<()> {
+    commands::check_auth(config)?;
 
-    Ok(commands::submit(config).is_ok())
+    commands::submit(config, args)
 }
 
-fn delete(config: &Config) -> Result<bool> {
-    commands::check_auth(config);
+fn delete(config: &Config) -> TickResult<()> {
+    commands::check_auth(config)?;
 
-    Ok(commands::delete_entry(config).is_ok())
+    commands::delete_entry(config)
 }
 
+fn check(config: &Config, args: &CheckArgs) -> TickResult<()> {
+    commands::check_auth(config)?;
+
+    commands::check_day(args)
+}
+
+fn start(config: &Config, args: &TimerArgs) -> TickResult<()> {
+    commands::check_auth(config)?;
+
+    commands::start_timer(config, args)
+}
+
+fn stop(config: &Config) -> TickResult<()> {
+    commands::check_auth(config)?;
+
+    commands::stop_timer()
+}
+
+fn switch(config: &Config, args: &TimerArgs) -> TickResult<()> {
+    commands::check_auth(config)?;
+
+    commands::switch_timer(config, args)
+}
+
+fn pull(config: &Config, args: &PullArgs) -> TickResult<()> {
+    commands::check_auth(config)?;
+
+    commands::pull_entries(config, args)
+}
+
+fn report(config: &Config, args: &ReportArgs) -> TickResult<()> {
+    commands::check_auth(config)?;
+
+    commands::report(config, args)
+}
+
+fn export(config: &Config, args: &ExportArgs) -> TickResult<()> {
+    commands::check_auth(config)?;
+
+    commands::export(args)
+}
+
+fn import(config: &Config, args: &ImportArgs) -> TickResult<()> {
+    commands::check_auth(config)?;
+
+    commands::import_entries(config, args)
+}
+
+fn manage_rounding(config: &mut Config, command: &RoundingCommand) -> TickResult<()> {
+    commands::check_auth(config)?;
+
+    match command {
+        RoundingCommand::List => commands::list_rounding(config),
+        RoundingCommand::Set(args) => commands::set_rounding(config, args),
+        RoundingCommand::Clear { project } => commands::clear_rounding(config, project.as_ref()),
+    }
+}
+
+fn manage_templates(config: &mut Config, command: &TemplateCommand) -> TickResult<()> {
+    match command {
+        TemplateCommand::Add(args) => {
+            // Only adding looks up the project and task in Tick.
+            commands::check_auth(config)?;
+            commands::add_template(config, args)
+        }
+        TemplateCommand::List => commands::list_templates(config),
+        TemplateCommand::Remove { name } => commands::remove_template(config, name),
+    }
+}
+
+fn manage_profiles(current: Option<&str>, command: &ProfileCommand) -> TickResult<()> {
+    match command {
+        ProfileCommand::List => commands::list_profiles(current),
+        ProfileCommand::Add { name } => commands::add_profile(name),
+        ProfileCommand::Remove { name } => commands::remove_profile(name),
+        ProfileCommand::Default { name } => commands::set_default_profile(name),
+    }
+}
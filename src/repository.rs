@@ -1,37 +1,115 @@
-use std::{io::ErrorKind, path::PathBuf};
-use tick_cli::EntryList;
-use crate::files::{self, FileError};
+use std::{io::ErrorKind, path::{Path, PathBuf}};
+use chrono::NaiveDate;
+use tick_cli::{EntryList, Timer};
+use crate::{error::{TickError, TickResult}, files::{self, FileError}};
 
-pub fn load_entry_list(path: &PathBuf) -> Result<EntryList, FileError> {
+const TIMER_FILENAME: &str = "timer.json";
+
+pub fn load_entry_list(path: &PathBuf) -> TickResult<EntryList> {
     match files::read_from_documents(path) {
-        Ok(data) => {
-            Ok(serde_json::from_str(data.as_str()).expect("Unable to parse file to json"))
-        },
-        Err(error) => match error {
-            FileError::FileError(_) => Err(error),
-            FileError::IoError(io_err) => {
-                if io_err.kind() == ErrorKind::NotFound {
-                    Ok(EntryList::empty())
-                } else {
-                    panic!("Problem opening the file: {:?}", io_err);
-                }
+        Ok(data) => serde_json::from_str(data.as_str())
+            .map_err(|e| TickError::Parse(format!("Cannot read {}: {}", path.display(), e))),
+        Err(FileError::IoError(io_err)) if io_err.kind() == ErrorKind::NotFound => Ok(EntryList::empty()),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Loads the entry lists of every day from `from` up to and including `to`,
+/// together with the day's filename and path. Days without a file are empty.
+pub fn load_entry_lists(from: &NaiveDate, to: &NaiveDate) -> TickResult<Vec<(String, PathBuf, EntryList)>> {
+    from.iter_days()
+        .take_while(|date| date <= to)
+        .map(|date| {
+            let filename = date.format("%Y-%m-%d").to_string();
+            let path = files::get_document_file_path_from(&filename)?;
+            let entries = load_entry_list(&path)?;
+
+            Ok((filename, path, entries))
+        })
+        .collect()
+}
+
+/// Loads the entry lists of every day that has a file, oldest first.
+pub fn load_all_entry_lists() -> TickResult<Vec<(String, PathBuf, EntryList)>> {
+    let base = files::get_document_file_path(None, None)?;
+    if !base.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut days = Vec::new();
+    for year_path in files::get_paths(&base)? {
+        if !year_path.is_dir() {
+            continue;
+        }
+
+        for month_path in files::get_paths(&year_path)? {
+            if !month_path.is_dir() {
+                continue;
+            }
+
+            for path in files::get_paths(&month_path)? {
+                // Skip anything else in there, like a .DS_Store.
+                let filename = match files::get_filename_from_path(&path) {
+                    Some(filename) if is_day_file(&path, &filename) => filename,
+                    _ => continue,
+                };
+                let entries = load_entry_list(&path)?;
+
+                days.push((filename, path, entries));
             }
         }
     }
+
+    Ok(days)
+}
+
+fn is_day_file(path: &Path, filename: &str) -> bool {
+    path.extension().is_some_and(|extension| extension == "json")
+        && NaiveDate::parse_from_str(filename, "%Y-%m-%d").is_ok()
 }
 
-pub fn store_entry_list(entries: &EntryList, path: &PathBuf) -> Result<(), ErrorKind> {
+pub fn store_entry_list(entries: &EntryList, path: &PathBuf) -> TickResult<()> {
     files::write_to_documents(
         path,
         serde_json::to_string_pretty(&entries).expect("Cannot serialize entries"),
-    )
-    .expect("Cannot write to file");
+    )?;
+
+    Ok(())
+}
+
+pub fn delete_entry_list(path: &PathBuf) -> TickResult<()> {
+    files::delete_documents(path)?;
+
+    Ok(())
+}
+
+pub fn load_timer() -> TickResult<Option<Timer>> {
+    let path = get_timer_path()?;
+
+    match files::read_from_data(&path) {
+        Ok(data) => serde_json::from_str(data.as_str())
+            .map(Some)
+            .map_err(|e| TickError::Parse(format!("Cannot read {}: {}", path.display(), e))),
+        Err(FileError::IoError(io_err)) if io_err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+pub fn store_timer(timer: &Timer) -> TickResult<()> {
+    files::write_to_data(
+        &get_timer_path()?,
+        serde_json::to_string_pretty(timer).expect("Cannot serialize timer"),
+    )?;
 
     Ok(())
 }
 
-pub fn delete_entry_list(path: &PathBuf) -> Result<(), ErrorKind> {
-    files::delete_documents(path).expect("Unable to delete file");
-    
+pub fn delete_timer() -> TickResult<()> {
+    files::delete_data(&get_timer_path()?)?;
+
     Ok(())
 }
+
+fn get_timer_path() -> TickResult<PathBuf> {
+    Ok(files::get_data_file_path(None, Some(&TIMER_FILENAME.to_string()))?)
+}
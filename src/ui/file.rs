@@ -1,37 +1,41 @@
 use std::path::PathBuf;
 
-use crate::files;
+use crate::{error::{TickError, TickResult}, files};
 use super::input;
 
-pub fn select_file() -> PathBuf {
-    let mut path = files::get_document_file_path(None, None);
+pub fn select_file() -> TickResult<PathBuf> {
+    let mut path = files::get_document_file_path(None, None)?;
+
+    if !path.is_dir() {
+        return Err(TickError::Other("No entries yet. Run `tick add` to add one.".to_string()));
+    }
 
     // Year
     path = files::get_document_file_path(
         Some(&path),
-        Some(&select(files::get_file_names(&path), "Select year")),
-    );
+        Some(&select(files::get_file_names(&path)?, "Select year")?),
+    )?;
 
     // Month
     path = files::get_document_file_path(
         Some(&path),
-        Some(&select(files::get_file_names(&path), "Select month")),
-    );
+        Some(&select(files::get_file_names(&path)?, "Select month")?),
+    )?;
 
     // File
     path = files::get_document_file_path(
         Some(&path),
-        Some(&select(files::get_file_names(&path), "Select date")),
-    );
+        Some(&select(files::get_file_names(&path)?, "Select date")?),
+    )?;
 
     path.set_extension("json");
 
-    path
+    Ok(path)
 }
 
-fn select(file_names: Vec<String>, prompt: &str) -> String {
+fn select(file_names: Vec<String>, prompt: &str) -> TickResult<String> {
     match input::fuzzy_select(prompt, &file_names, Some(0), false) {
-        Some(index) => file_names[index].clone(),
-        None => panic!("Nothing selected"),
+        Some(index) => Ok(file_names[index].clone()),
+        None => Err(TickError::Cancelled),
     }
 }
@@ -1,5 +1,5 @@
-use chrono::{NaiveDate, NaiveTime};
-use dialoguer::{theme::ColorfulTheme, Confirm, FuzzySelect, Input};
+use chrono::{Local, NaiveDate, NaiveTime, Timelike};
+use dialoguer::{console::style, theme::ColorfulTheme, Confirm, FuzzySelect, Input};
 
 pub fn fuzzy_select<T: ToString>(
     prompt: &str,
@@ -14,17 +14,30 @@ pub fn fuzzy_select<T: ToString>(
         .with_prompt(prompt)
         .items(items);
 
-    if default.is_some() {
-        select.default(default.unwrap());
+    if let Some(default) = default {
+        select.default(default);
     }
 
-    if opt == true {
+    if opt {
         select.interact_opt().unwrap()
     } else {
         Some(select.interact().unwrap())
     }
 }
 
+pub const DATE_FORMAT_ERROR: &str =
+    "Wrong date format. Please provide a date like 2023-01-30, today, yesterday, fri, last fri, -2d or 5/3";
+
+/// Reads a date as typed in the prompts, relative to today in the local
+/// timezone.
+pub fn parse_date(input: &str) -> Option<NaiveDate> {
+    tick_cli::parse_date(input, today())
+}
+
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
 pub fn date(prompt: &str, default: Option<&String>) -> Option<NaiveDate> {
     let theme = &ColorfulTheme::default();
     let mut input = Input::with_theme(theme);
@@ -32,25 +45,74 @@ pub fn date(prompt: &str, default: Option<&String>) -> Option<NaiveDate> {
     input
         .with_prompt(prompt)
         .validate_with(|input: &String| -> Result<(), &str> {
-            let date = NaiveDate::parse_from_str(&input, "%Y-%m-%d");
-            match date {
-                Ok(_date) => Ok(()),
-                Err(_e) => Err("Wrong date format. Please provide a date like 2023-01-30"),
+            match parse_date(input) {
+                Some(_) => Ok(()),
+                None => Err(DATE_FORMAT_ERROR),
             }
         });
 
     // Use inital text, instead of default so that the user
     // can change the input easier.
-    if default.is_some() {
-        input.with_initial_text(default.unwrap());
+    if let Some(default) = default {
+        input.with_initial_text(default);
+    }
+
+    let input = input.interact().unwrap();
+    let date = parse_date(&input).unwrap();
+
+    // Show what loosely typed input turned into.
+    let formatted = date.format("%Y-%m-%d").to_string();
+    if input.trim() != formatted {
+        println!("  {} {}", style("=").dim(), style(date.format("%a %Y-%m-%d")).bold());
     }
 
-    let date = input.interact().unwrap();
+    Some(date)
+}
+
+pub const TIME_FORMAT_ERROR: &str =
+    "Wrong time format. Please provide a time like 9:15, 930, 9:30pm, now, +15m, prev or 01:30+1";
 
-    Some(NaiveDate::parse_from_str(&date, "%Y-%m-%d").unwrap())
+/// Reads a time as typed in the prompts, where `previous` is the end time of
+/// the previous entry that `prev` refers to.
+pub fn parse_time(input: &str, previous: Option<NaiveTime>) -> Option<NaiveTime> {
+    tick_cli::parse_time(input, now()?, previous)
 }
 
-pub fn time(prompt: &str, default: Option<&String>, opt: bool) -> Option<NaiveTime> {
+pub fn time(
+    prompt: &str,
+    default: Option<&String>,
+    opt: bool,
+    previous: Option<NaiveTime>,
+) -> Option<NaiveTime> {
+    input_time(prompt, default, opt, |input| parse_time(input, previous).map(|time| (time, false)))
+        .map(|(time, _)| time)
+}
+
+/// Like `time`, but also takes times on the next day, like `01:30+1`.
+pub fn end_time(
+    prompt: &str,
+    default: Option<&String>,
+    opt: bool,
+    previous: Option<NaiveTime>,
+) -> Option<(NaiveTime, bool)> {
+    input_time(prompt, default, opt, |input| parse_end_time(input, previous))
+}
+
+/// Reads an end time as typed in the prompts, see `parse_time`.
+pub fn parse_end_time(input: &str, previous: Option<NaiveTime>) -> Option<(NaiveTime, bool)> {
+    tick_cli::parse_end_time(input, now()?, previous)
+}
+
+fn now() -> Option<NaiveTime> {
+    Local::now().time().with_second(0)?.with_nanosecond(0)
+}
+
+fn input_time(
+    prompt: &str,
+    default: Option<&String>,
+    opt: bool,
+    parse: impl Fn(&str) -> Option<(NaiveTime, bool)>,
+) -> Option<(NaiveTime, bool)> {
     let theme = &ColorfulTheme::default();
     let mut input = Input::with_theme(theme);
 
@@ -58,30 +120,64 @@ pub fn time(prompt: &str, default: Option<&String>, opt: bool) -> Option<NaiveTi
         .allow_empty(opt)
         .with_prompt(prompt)
         .validate_with(|input: &String| -> Result<(), &str> {
-            if opt == true && input.is_empty() {
+            if opt && input.is_empty() {
                 return Ok(());
             }
 
-            let time = NaiveTime::parse_from_str(&input, "%H:%M");
-            match time {
-                Ok(_) => Ok(()),
-                Err(_) => Err("Wrong time format. Please provide a time like 9:15"),
+            match parse(input) {
+                Some(_) => Ok(()),
+                None => Err(TIME_FORMAT_ERROR),
             }
         });
 
     // Use inital text, instead of default so that the user
     // can change the input easier.
-    if default.is_some() {
-        input.with_initial_text(default.unwrap());
+    if let Some(default) = default {
+        input.with_initial_text(default);
     }
 
-    let time = input.interact().unwrap();
+    let input = input.interact().unwrap();
 
-    if time.is_empty() {
-        None
-    } else {
-        Some(NaiveTime::parse_from_str(&time, "%H:%M").unwrap())
+    if input.is_empty() {
+        return None;
+    }
+
+    let (time, next_day) = parse(&input).unwrap();
+
+    // Show what loosely typed input turned into.
+    let formatted = format_time(&time, next_day);
+    if input.trim() != formatted {
+        println!("  {} {}", style("=").dim(), style(formatted).bold());
+    }
+
+    Some((time, next_day))
+}
+
+/// Formats a time the way the prompts take it, with `+1` for the next day.
+pub fn format_time(time: &NaiveTime, next_day: bool) -> String {
+    format!("{}{}", time.format("%H:%M"), if next_day { "+1" } else { "" })
+}
+
+pub fn hours(prompt: &str, default: Option<&String>) -> f64 {
+    let theme = &ColorfulTheme::default();
+    let mut input = Input::with_theme(theme);
+
+    input
+        .with_prompt(prompt)
+        .validate_with(|input: &String| -> Result<(), &str> {
+            match tick_cli::parse_hours(input) {
+                Some(_) => Ok(()),
+                None => Err("Wrong hours format. Please provide hours like 2.5 or 1h30m, up to 24"),
+            }
+        });
+
+    if let Some(default) = default {
+        input.with_initial_text(default);
     }
+
+    let input: String = input.interact().unwrap();
+
+    tick_cli::parse_hours(&input).unwrap()
 }
 
 pub fn default(prompt: &str, default: Option<&String>) -> String {
@@ -92,18 +188,20 @@ pub fn default(prompt: &str, default: Option<&String>) -> String {
         .allow_empty(true)
         .with_prompt(prompt);
 
-    if default.is_some() {
-        input.with_initial_text(default.unwrap());
+    if let Some(default) = default {
+        input.with_initial_text(default);
     }
 
     input.interact().unwrap()
 }
 
-pub fn confirm(prompt: &str) -> Option<bool> {
-    Some(Confirm::with_theme(&ColorfulTheme::default())
+/// Asks a yes or no question, where no answer at all, like when there's no
+/// terminal to ask it on, counts as no.
+pub fn confirm(prompt: &str) -> bool {
+    Confirm::with_theme(&ColorfulTheme::default())
         .with_prompt(prompt)
         .default(true)
         .wait_for_newline(true)
         .interact()
-        .unwrap())
+        .unwrap_or(false)
 }
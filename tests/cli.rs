@@ -0,0 +1,1012 @@
+mod common;
+
+use common::{mock_server, stderr, stdout, stdout_lines, TestEnv};
+use serde_json::json;
+use std::fs;
+
+#[test]
+fn test_requires_login() {
+    let env = TestEnv::new();
+
+    let output = env.tick(&["list"]);
+
+    assert_eq!(Some(3), output.status.code());
+    assert!(stderr(&output).contains("tick login"));
+}
+
+#[test]
+fn test_login_with_token() {
+    let env = TestEnv::new();
+    let subscription = mock_server::SUBSCRIPTION_ID.to_string();
+    // What's in the environment mustn't stand in for the token being checked.
+    let login = |token: &str| {
+        env.command()
+            .env("TICK_API_TOKEN", mock_server::API_TOKEN)
+            .env("TICK_SUBSCRIPTION_ID", "acme")
+            .env("TICK_BASE_URL", env.server.url())
+            .args(["login", "--token", token, "--subscription", &subscription])
+            .output()
+            .unwrap()
+    };
+
+    let output = login("wrong-token");
+    assert_eq!(Some(3), output.status.code());
+    assert!(stderr(&output).contains("rejected"), "{}", stderr(&output));
+
+    let output = login(mock_server::API_TOKEN);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("Logged in as"));
+    assert!(env.read_config().contains(mock_server::API_TOKEN));
+
+    let output = env
+        .command()
+        .env("TICK_BASE_URL", env.server.url())
+        .args(["pull", "--from", "2024-05-03"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let output = env.tick(&["login", "--token", mock_server::API_TOKEN]);
+    assert_eq!(Some(2), output.status.code());
+}
+
+// `script` is how the prompts get a terminal, and its flags are util-linux's.
+#[cfg(target_os = "linux")]
+#[test]
+fn test_login_with_password_and_switch_account() {
+    let env = TestEnv::new();
+    env.write_config(&format!("version = 0\nsubscription_id = 0\nbase_url = '{}'\n", env.server.url()));
+    let credentials = [
+        ("Email", &format!("{}\r", mock_server::EMAIL)[..]),
+        ("Password", &format!("{}\r", mock_server::PASSWORD)[..]),
+    ];
+
+    let mut answers = credentials.to_vec();
+    answers.push(("Select a subscription", "\r"));
+    let (code, shown) = env.tick_in_terminal(&["login"], &answers);
+    assert_eq!(Some(0), code, "{}", shown);
+    assert!(shown.contains("Logged in as") && shown.contains(mock_server::COMPANY), "{}", shown);
+
+    let config = env.read_config();
+    assert!(config.contains(&format!("subscription_id = {}", mock_server::SUBSCRIPTION_ID)));
+    assert!(config.contains(mock_server::OTHER_COMPANY));
+    assert!(!config.contains(mock_server::OTHER_API_TOKEN));
+
+    let mut answers = vec![("Select a subscription", "Globex\r")];
+    answers.extend(credentials);
+    let (code, shown) = env.tick_in_terminal(&["account", "switch"], &answers);
+    assert_eq!(Some(0), code, "{}", shown);
+    assert!(shown.contains("Switched to") && shown.contains(mock_server::OTHER_COMPANY), "{}", shown);
+
+    let config = env.read_config();
+    assert!(config.contains(&format!("subscription_id = {}", mock_server::OTHER_SUBSCRIPTION_ID)));
+    assert!(config.contains(mock_server::OTHER_API_TOKEN));
+    assert!(!config.contains(mock_server::API_TOKEN));
+
+    let output = env.tick(&["pull", "--from", "2024-05-03"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let requests = env.server.requests();
+    assert!(requests.last().unwrap().path.starts_with(&format!("/{}/", mock_server::OTHER_SUBSCRIPTION_ID)));
+
+    // Switching needs the token of a role Tick still lists.
+    env.server.state().roles.truncate(1);
+    let mut answers = vec![("Select a subscription", "Globex\r")];
+    answers.extend(credentials);
+    let (code, shown) = env.tick_in_terminal(&["account", "switch"], &answers);
+    assert_eq!(Some(3), code, "{}", shown);
+    assert!(shown.contains("no access"), "{}", shown);
+}
+
+#[test]
+fn test_add_profile_that_is_in_use() {
+    let env = TestEnv::new();
+
+    let output = env
+        .command()
+        .env("TICK_PROFILE", "work")
+        .args(["profile", "add", "work"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let output = env.tick(&["--profile", "work", "profile", "list"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("* work"));
+
+    let output = env.tick(&["--profile", "home", "list"]);
+    assert_eq!(Some(1), output.status.code());
+    assert!(stderr(&output).contains("Unknown profile"));
+}
+
+#[test]
+fn test_profiles_keep_credentials_and_entries_apart() {
+    let env = TestEnv::logged_in();
+    let default_config = env.read_config();
+    let subscription = mock_server::SUBSCRIPTION_ID.to_string();
+    let tick = |args: &[&str]| {
+        env.command()
+            .env("TICK_BASE_URL", env.server.url())
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = env.tick(&["profile", "add", "work"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let output = tick(&["--profile", "work", "login", "--token", mock_server::API_TOKEN, "--subscription", &subscription]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let output = tick(&[
+        "--profile", "work",
+        "add",
+        "--date", "2024-05-03",
+        "--project", "acme",
+        "--task", "dev",
+        "--start", "09:00",
+        "--end", "10:30",
+        "--yes",
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let work_config = fs::read_to_string(env.home().join(".config/tick-cli/work-config.toml")).unwrap();
+    assert!(work_config.contains(mock_server::API_TOKEN));
+    assert!(env.home().join("Documents/Tick-work/2024/05/2024-05-03.json").exists());
+
+    assert_eq!(default_config, env.read_config());
+    assert!(!env.documents_dir().exists());
+
+    let output = env.tick(&["--profile", "work", "logout"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(default_config, env.read_config());
+}
+
+#[test]
+fn test_add_entry_with_flags() {
+    let env = TestEnv::logged_in();
+
+    let output = env.tick(&[
+        "add",
+        "--date", "2024-05-03",
+        "--project", "acme",
+        "--task", "dev",
+        "--start", "09:00",
+        "--end", "10:30",
+        "--notes", "Refactoring",
+        "--yes",
+    ]);
+    assert!(output.status.success(), "{}", stdout(&output));
+
+    let day = env.read_day("2024-05-03");
+    let entry = &day["entries"][0];
+    assert_eq!("Acme", entry["project_name"]);
+    assert_eq!(11, entry["task_id"]);
+    assert_eq!("Development", entry["task_name"]);
+    assert_eq!("09:00:00", entry["start_time"]);
+    assert_eq!("10:30:00", entry["end_time"]);
+    assert_eq!("Refactoring", entry["notes"]);
+
+    let requests = env.server.requests();
+    assert_eq!(
+        format!("/{}/api/v2/projects.json", mock_server::SUBSCRIPTION_ID),
+        requests[0].path,
+    );
+}
+
+#[test]
+fn test_add_entry_follows_pagination() {
+    let env = TestEnv::logged_in();
+    env.server.state().projects = (1..=150)
+        .map(|id| json!({ "id": id, "name": format!("Project {:03}", id) }))
+        .collect();
+    env.server.state().tasks.insert(142, vec![json!({ "id": 1421, "name": "Design" })]);
+
+    let output = env.tick(&[
+        "add", "--date", "2024-05-03", "--project", "Project 142", "--task", "Design", "--start", "9:00", "--yes",
+    ]);
+    assert!(output.status.success(), "{}", stdout(&output));
+
+    let day = env.read_day("2024-05-03");
+    assert_eq!("Project 142", day["entries"][0]["project_name"]);
+
+    let project_pages: Vec<String> = env
+        .server
+        .requests()
+        .iter()
+        .filter(|r| r.path.ends_with("/projects.json"))
+        .map(|r| r.query.clone())
+        .collect();
+    assert_eq!(vec!["page=1", "page=2"], project_pages);
+}
+
+#[test]
+fn test_pagination_ignored_by_server() {
+    let env = TestEnv::logged_in();
+    env.server.state().projects = (1..=100)
+        .map(|id| json!({ "id": id, "name": format!("Project {:03}", id) }))
+        .collect();
+    env.server.state().ignore_page = true;
+
+    let output = env.tick(&[
+        "add", "--date", "2024-05-03", "--project", "Project 042", "--task", "Design", "--start", "9:00", "--yes",
+    ]);
+    assert_eq!(Some(7), output.status.code());
+    assert!(stderr(&output).contains("kept sending pages"), "{}", stderr(&output));
+    assert_eq!(100, env.server.requests().len());
+}
+
+#[test]
+fn test_pages_are_cached() {
+    let env = TestEnv::logged_in();
+    let args = [
+        "add", "--date", "2024-05-03", "--project", "Acme", "--task", "Meetings", "--start", "9:00", "--yes",
+    ];
+
+    assert!(env.tick(&args).status.success());
+    let first_run = env.server.requests().len();
+    assert!(env.tick(&args).status.success());
+
+    let revalidated = env.server.requests()[first_run..]
+        .iter()
+        .all(|r| r.headers.contains_key("if-none-match"));
+    assert!(revalidated);
+    assert_eq!(2, env.read_day("2024-05-03")["entries"].as_array().unwrap().len());
+}
+
+#[test]
+fn test_add_entry_with_ambiguous_project() {
+    let env = TestEnv::logged_in();
+
+    let output = env.tick(&[
+        "add",
+        "--date", "2024-05-03",
+        "--project", "globex",
+        "--start", "09:00",
+        "--yes",
+    ]);
+
+    assert_eq!(Some(1), output.status.code());
+    assert!(stderr(&output).contains("Globex Website"));
+    assert!(stderr(&output).contains("Globex App"));
+    assert!(!env.day_path("2024-05-03").exists());
+}
+
+#[test]
+fn test_base_url_from_environment() {
+    let env = TestEnv::logged_in();
+    env.write_config(&format!(
+        "version = 0\nsubscription_id = {}\napi_key = '{}'\nbase_url = 'http://127.0.0.1:1'\n",
+        mock_server::SUBSCRIPTION_ID,
+        mock_server::API_TOKEN,
+    ));
+
+    let output = env
+        .command()
+        .env("TICK_BASE_URL", env.server.url())
+        .args(["add", "--date", "2024-05-03", "--project", "Acme", "--task", "Meetings", "--start", "9:00", "--yes"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert_eq!(12, env.read_day("2024-05-03")["entries"][0]["task_id"]);
+}
+
+#[test]
+fn test_pull_entries() {
+    let env = TestEnv::logged_in();
+    env.server.state().entries = vec![
+        json!({ "id": 1000, "date": "2024-05-03", "task_id": 11, "hours": 1.5, "notes": "From the web", "user_id": 7 }),
+        json!({ "id": 1001, "date": "2024-05-03", "task_id": 11, "hours": 2.0, "notes": "Standup", "user_id": 7 }),
+        json!({ "id": 1003, "date": "2024-05-03", "task_id": 21, "hours": 3.0, "notes": "Someone else", "user_id": 8 }),
+    ];
+    env.write_day("2024-05-03", json!({
+        "entries": [
+            submitted_entry(1001, "09:00:00", "10:00:00", "Standup"),
+            submitted_entry(1002, "10:00:00", "11:00:00", "Removed"),
+        ],
+        "all_submitted": true,
+    }));
+
+    let output = env.tick(&["pull", "--from", "2024-05-01", "--to", "2024-05-05"]);
+    assert!(output.status.success(), "{}", stdout(&output));
+
+    let entries = env.read_day("2024-05-03")["entries"].as_array().unwrap().clone();
+    assert_eq!(3, entries.len());
+    assert_eq!(1000, entries[2]["tick_id"]);
+    assert_eq!("Acme", entries[2]["project_name"]);
+    assert_eq!("Development", entries[2]["task_name"]);
+    assert_eq!(serde_json::Value::Null, entries[2]["start_time"]);
+    assert_eq!(1.5, entries[2]["hours"]);
+
+    let stdout = stdout(&output);
+    assert!(stdout.contains("Pulled 1 entries"));
+    let (changed, deleted) = stdout.split_once("Deleted from Tick:").unwrap();
+    assert!(changed.contains("Standup"));
+    assert!(deleted.contains("Removed"));
+}
+
+#[test]
+fn test_pull_entries_of_one_of_several_users() {
+    let env = TestEnv::logged_in();
+    env.server.state().users.push(json!({ "id": 8, "first_name": "John", "last_name": "Roe" }));
+    env.server.state().entries = vec![
+        json!({ "id": 1000, "date": "2024-05-03", "task_id": 11, "hours": 1.5, "notes": "Mine", "user_id": 7 }),
+        json!({ "id": 1001, "date": "2024-05-03", "task_id": 21, "hours": 3.0, "notes": "Someone else", "user_id": 8 }),
+    ];
+
+    let output = env.tick(&["pull", "--from", "2024-05-03"]);
+    assert_eq!(Some(1), output.status.code());
+    assert!(stderr(&output).contains("8 (John)"), "{}", stderr(&output));
+    assert!(!env.day_path("2024-05-03").exists());
+
+    let output = env.tick(&["pull", "--from", "2024-05-03", "--user", "7"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let entries = env.read_day("2024-05-03")["entries"].as_array().unwrap().clone();
+    assert_eq!(1, entries.len());
+    assert_eq!("Mine", entries[0]["notes"]);
+}
+
+#[test]
+fn test_report_by_project() {
+    let env = TestEnv::logged_in();
+    env.write_day("2024-05-03", json!({
+        "entries": [
+            entry("Acme", 11, "Development", "09:00:00", Some("10:30:00")),
+            entry("Acme", 12, "Meetings", "10:30:00", Some("11:00:00")),
+            entry("Globex Website", 21, "Design", "11:00:00", Some("12:00:00")),
+        ],
+        "all_submitted": false,
+    }));
+    env.write_day("2024-05-06", json!({
+        "entries": [entry("Acme", 11, "Development", "09:00:00", Some("11:00:00"))],
+        "all_submitted": false,
+    }));
+    env.write_day("2024-05-10", json!({
+        "entries": [entry("Acme", 11, "Development", "09:00:00", Some("17:00:00"))],
+        "all_submitted": false,
+    }));
+
+    let output = env.tick(&["report", "--from", "2024-05-01", "--to", "2024-05-07"]);
+    assert!(output.status.success(), "{}", stdout(&output));
+
+    let lines = stdout_lines(&output);
+    assert!(lines.contains(&"Acme 4.00".to_string()));
+    assert!(lines.contains(&"Development 3.50".to_string()));
+    assert!(lines.contains(&"Meetings 0.50".to_string()));
+    assert!(lines.contains(&"Globex Website 1.00".to_string()));
+    assert!(lines.contains(&"Total 5.00".to_string()));
+
+    let output = env.tick(&["report", "--from", "2024-05-01", "--to", "2024-05-07", "--by", "day"]);
+    let lines = stdout_lines(&output);
+    assert!(lines.contains(&"2024-05-03 3.00".to_string()));
+    assert!(lines.contains(&"Acme | Development 2.00".to_string()));
+}
+
+#[test]
+fn test_export_formats() {
+    let env = TestEnv::logged_in();
+    let mut submitted = submitted_entry(1001, "10:30:00", "11:00:00", "Standup, daily");
+    submitted["task_name"] = json!("Meetings");
+    env.write_day("2024-05-03", json!({
+        "entries": [entry("Acme", 11, "Development", "09:00:00", Some("10:30:00")), submitted],
+        "all_submitted": false,
+    }));
+
+    let output = env.tick(&["export", "--format", "csv", "--from", "2024-05-03"]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert_eq!(
+        "date,start,end,hours,project,task,notes,tick_id,state\n\
+         2024-05-03,09:00,10:30,1.50,Acme,Development,,,pending\n\
+         2024-05-03,10:30,11:00,0.50,Acme,Meetings,\"Standup, daily\",1001,submitted\n",
+        stdout(&output),
+    );
+
+    let output = env.tick(&["export", "--format", "json", "--from", "2024-05-03"]);
+    let rows: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(1.5, rows[0]["hours"]);
+    assert_eq!(1001, rows[1]["tick_id"]);
+
+    let path = env.home().join("export.ics");
+    let output = env.tick(&["export", "--format", "ics", "--from", "2024-05-03", "-o", path.to_str().unwrap()]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    let ics = std::fs::read_to_string(path).unwrap();
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert_eq!(2, ics.matches("BEGIN:VEVENT").count());
+    assert!(ics.contains("DTSTART:20240503T090000\r\nDTEND:20240503T103000\r\n"));
+    assert!(ics.contains("SUMMARY:Acme | Meetings\r\nDESCRIPTION:Standup\\, daily\r\n"));
+    assert!(ics.contains("UID:tick-1001@tick-cli"));
+}
+
+#[test]
+fn test_import_toggl_csv() {
+    let env = TestEnv::logged_in();
+    let path = env.home().join("toggl.csv");
+    std::fs::write(&path, "\
+User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags
+Jane,jane@example.com,,Acme,Development,\"Fix bug, again\",Yes,2024-05-03,09:00:00,2024-05-03,10:30:00,01:30:00,
+Jane,jane@example.com,,Unknown,Design,Lunch,No,2024-05-03,12:00:00,2024-05-03,12:30:00,00:30:00,
+Jane,jane@example.com,,Globex Website,Design,,Yes,2024-05-06,13:00:00,2024-05-06,14:00:00,01:00:00,
+").unwrap();
+
+    let output = env.tick(&["import", "--format", "toggl-csv", "--dry-run", path.to_str().unwrap()]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(stdout(&output).contains("Would import 3 entries into 2 days"));
+    assert!(!env.day_path("2024-05-03").exists());
+
+    let output = env.tick(&["import", "--format", "toggl-csv", "--yes", path.to_str().unwrap()]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(stdout(&output).contains("No project found for \"Unknown\""));
+
+    let entries = env.read_day("2024-05-03")["entries"].as_array().unwrap().clone();
+    assert_eq!(2, entries.len());
+    assert_eq!(11, entries[0]["task_id"]);
+    assert_eq!("Fix bug, again", entries[0]["notes"]);
+    assert_eq!("10:30:00", entries[0]["end_time"]);
+    assert_eq!(serde_json::Value::Null, entries[1]["project_name"]);
+    assert_eq!(21, env.read_day("2024-05-06")["entries"][0]["task_id"]);
+
+    assert!(stdout(&output).contains("Imported 3 entries"));
+
+    // Importing again doesn't duplicate anything.
+    let output = env.tick(&["import", "--format", "toggl-csv", "--yes", path.to_str().unwrap()]);
+    assert!(stdout(&output).contains("Imported 0 entries"));
+    assert!(stdout(&output).contains("Skipped 3 entries"));
+    assert_eq!(2, env.read_day("2024-05-03")["entries"].as_array().unwrap().len());
+}
+
+#[test]
+fn test_import_csv_with_column_mapping() {
+    let env = TestEnv::logged_in();
+    let path = env.home().join("hours.csv");
+    std::fs::write(&path, "day,from,hours,client,activity,notes\n05/03/2024,9:00,1.5,acme,meet,Planning\n").unwrap();
+
+    let output = env.tick(&[
+        "import", path.to_str().unwrap(), "--yes",
+        "--column", "date=day", "--column", "start=from",
+        "--column", "project=client", "--column", "task=activity",
+    ]);
+    assert_eq!(Some(7), output.status.code());
+    assert!(stderr(&output).contains("--date-format"), "{}", stderr(&output));
+
+    let output = env.tick(&[
+        "import", path.to_str().unwrap(), "--yes", "--date-format", "%m/%d/%Y",
+        "--column", "date=day", "--column", "start=from",
+        "--column", "project=client", "--column", "task=activity",
+    ]);
+    assert!(output.status.success(), "{}", stdout(&output));
+
+    let entry = &env.read_day("2024-05-03")["entries"][0];
+    assert_eq!(12, entry["task_id"]);
+    assert_eq!("10:30:00", entry["end_time"]);
+}
+
+#[test]
+fn test_import_own_csv_export() {
+    let env = TestEnv::logged_in();
+    env.write_day("2024-05-03", json!({
+        "entries": [entry("Acme", 11, "Development", "09:00:00", Some("10:30:00"))],
+        "all_submitted": false,
+    }));
+    env.tick(&["add", "--date", "2024-05-03", "--project", "acme", "--task", "meet", "--hours", "2.5", "--yes"]);
+    let path = env.home().join("export.csv");
+
+    let output = env.tick(&["export", "--format", "csv", "--from", "2024-05-03", "--output", path.to_str().unwrap()]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    fs::remove_file(env.day_path("2024-05-03")).unwrap();
+
+    let output = env.tick(&["import", "--yes", path.to_str().unwrap()]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("Imported 2 entries"));
+
+    let entries = env.read_day("2024-05-03")["entries"].as_array().unwrap().clone();
+    assert_eq!("10:30:00", entries[0]["end_time"]);
+    assert_eq!(12, entries[1]["task_id"]);
+    assert_eq!(2.5, entries[1]["hours"]);
+    assert_eq!(serde_json::Value::Null, entries[1]["start_time"]);
+}
+
+#[test]
+fn test_add_entry_from_template() {
+    let env = TestEnv::logged_in();
+
+    let output = env.tick(&[
+        "template", "add", "standup",
+        "--project", "acme", "--task", "meet",
+        "--notes", "Daily standup", "--duration", "15m",
+    ]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(env.read_config().contains("[[templates]]"));
+
+    let output = env.tick(&["template", "list"]);
+    assert!(stdout(&output).contains("standup: Acme | Meetings: Daily standup (15m)"));
+
+    let output = env.tick(&["add", "--template", "standup", "--date", "2024-05-03", "--start", "09:00", "--yes"]);
+    assert!(output.status.success(), "{}", stdout(&output));
+
+    let entry = &env.read_day("2024-05-03")["entries"][0];
+    assert_eq!(12, entry["task_id"]);
+    assert_eq!("09:15:00", entry["end_time"]);
+    assert_eq!("Daily standup", entry["notes"]);
+
+    env.tick(&["template", "add", "release", "--project", "acme", "--task", "dev", "--duration", "2h"]);
+    let output = env.tick(&["add", "--template", "release", "--date", "2024-05-03", "--start", "23:00", "--yes"]);
+    assert!(output.status.success(), "{}", stdout(&output));
+
+    let entry = &env.read_day("2024-05-03")["entries"][1];
+    assert_eq!("01:00:00", entry["end_time"]);
+    assert_eq!(true, entry["ends_next_day"]);
+
+    let output = env.tick(&["add", "--template", "retro", "--date", "2024-05-03", "--start", "10:00", "--yes"]);
+    assert!(!output.status.success());
+
+    let output = env.tick(&["logout"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!env.read_config().contains(mock_server::API_TOKEN));
+
+    let output = env.tick(&["template", "list"]);
+    assert!(stdout(&output).contains("standup: Acme | Meetings: Daily standup (15m)"));
+}
+
+#[test]
+fn test_timer_across_runs() {
+    let env = TestEnv::logged_in();
+    let timer_path = env.home().join(".local/share/Tick/timer.json");
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+    let output = env.tick(&["start", "--project", "acme", "--task", "dev", "--notes", "Refactoring"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(fs::read_to_string(&timer_path).unwrap().contains("Development"));
+
+    // Without anyone to confirm it, the running timer is left alone.
+    let output = env.tick(&["start", "--project", "acme", "--task", "meet"]);
+    assert_eq!(Some(8), output.status.code());
+    assert!(fs::read_to_string(&timer_path).unwrap().contains("Development"));
+    assert!(!env.day_path(&today).exists());
+
+    let output = env.tick(&["switch", "--project", "acme", "--task", "meet", "--notes", "Standup"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(fs::read_to_string(&timer_path).unwrap().contains("Meetings"));
+
+    let entries = &env.read_day(&today)["entries"];
+    assert_eq!(1, entries.as_array().unwrap().len());
+    assert_eq!(11, entries[0]["task_id"]);
+    assert_eq!("Refactoring", entries[0]["notes"]);
+
+    let output = env.tick(&["stop"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!timer_path.exists());
+
+    let entries = &env.read_day(&today)["entries"];
+    assert_eq!(2, entries.as_array().unwrap().len());
+    assert_eq!(12, entries[1]["task_id"]);
+    assert_eq!("Standup", entries[1]["notes"]);
+
+    let output = env.tick(&["stop"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("No timer is running"));
+}
+
+#[test]
+fn test_check_day() {
+    let env = TestEnv::logged_in();
+    env.write_day("2024-05-03", json!({
+        "entries": [
+            entry("Acme", 11, "Development", "09:00:00", Some("10:30:00")),
+            entry("Acme", 12, "Meetings", "10:00:00", Some("11:00:00")),
+            entry("Globex Website", 21, "Design", "12:00:00", Some("13:00:00")),
+        ],
+        "all_submitted": false,
+    }));
+
+    let output = env.tick(&["check", "2024-05-03"]);
+    assert_eq!(Some(5), output.status.code());
+    assert!(stdout(&output).contains("The entries at 09:00 and 10:00 overlap from 10:00 to 10:30"));
+    assert!(stdout(&output).contains("Nothing is booked from 11:00 to 12:00"));
+
+    let output = env.tick(&["check", "2024-05-06"]);
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_add_entry_crossing_midnight() {
+    let env = TestEnv::logged_in();
+
+    let output = env.tick(&[
+        "add", "--date", "2024-05-03", "--project", "acme", "--task", "dev",
+        "--start", "22:00", "--end", "01:30+1", "--notes", "Deploy", "--yes",
+    ]);
+    assert!(output.status.success(), "{}", stdout(&output));
+
+    let entry = &env.read_day("2024-05-03")["entries"][0];
+    assert_eq!("01:30:00", entry["end_time"]);
+    assert_eq!(true, entry["ends_next_day"]);
+
+    let output = env.tick(&["report", "--from", "2024-05-03"]);
+    assert!(stdout(&output).contains("3.50"), "{}", stdout(&output));
+}
+
+#[test]
+fn test_add_break() {
+    let env = TestEnv::logged_in();
+    env.write_day("2024-05-03", json!({
+        "entries": [entry("Acme", 11, "Development", "09:00:00", Some("12:00:00"))],
+        "all_submitted": false,
+    }));
+
+    let output = env.tick(&[
+        "add", "--date", "2024-05-03", "--kind", "break",
+        "--start", "12:00", "--end", "12:30", "--yes",
+    ]);
+    assert!(output.status.success(), "{}", stdout(&output));
+
+    let entry = &env.read_day("2024-05-03")["entries"][1];
+    assert_eq!("break", entry["kind"]);
+    assert_eq!(serde_json::Value::Null, entry["task_id"]);
+
+    let output = env.tick(&["check", "2024-05-03"]);
+    assert!(stdout(&output).contains("12:00 - 12:30: Break"));
+
+    let output = env.tick(&["report", "--from", "2024-05-03"]);
+    assert!(!stdout(&output).contains("(no project)"), "{}", stdout(&output));
+}
+
+#[test]
+fn test_add_entry_with_just_hours() {
+    let env = TestEnv::logged_in();
+    env.write_day("2024-05-03", json!({
+        "entries": [entry("Acme", 11, "Development", "09:00:00", Some("12:00:00"))],
+        "all_submitted": false,
+    }));
+
+    let output = env.tick(&[
+        "add", "--date", "2024-05-03", "--project", "acme", "--task", "dev",
+        "--hours", "2.5", "--notes", "Code review", "--yes",
+    ]);
+    assert!(output.status.success(), "{}", stdout(&output));
+
+    let entry = &env.read_day("2024-05-03")["entries"][1];
+    assert_eq!(2.5, entry["hours"]);
+    assert_eq!(serde_json::Value::Null, entry["start_time"]);
+
+    let output = env.tick(&["check", "2024-05-03"]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(stdout(&output).contains("Without times:"));
+
+    let output = env.tick(&["report", "--from", "2024-05-03"]);
+    assert!(stdout(&output).contains("5.50"), "{}", stdout(&output));
+
+    let output = env.tick(&["add", "--date", "2024-05-03", "--kind", "break", "--hours", "inf", "--yes"]);
+    assert_eq!(Some(2), output.status.code());
+    assert_eq!(2, env.read_day("2024-05-03")["entries"].as_array().unwrap().len());
+}
+
+#[test]
+fn test_submit_range() {
+    let env = TestEnv::logged_in();
+    env.write_day("2024-05-02", json!({
+        "entries": [submitted_entry(1001, "09:00:00", "10:00:00", "Standup")],
+        "all_submitted": true,
+    }));
+    env.write_day("2024-05-03", json!({
+        "entries": [
+            entry("Acme", 11, "Development", "09:00:00", Some("10:30:00")),
+            entry("Globex Website", 21, "Design", "10:30:00", Some("12:00:00")),
+        ],
+        "all_submitted": false,
+    }));
+    env.write_day("2024-05-06", json!({
+        "entries": [entry("Acme", 11, "Development", "09:00:00", Some("11:00:00"))],
+        "all_submitted": false,
+    }));
+    env.write_day("2024-05-13", json!({
+        "entries": [entry("Acme", 12, "Meetings", "09:00:00", Some("09:30:00"))],
+        "all_submitted": false,
+    }));
+
+    let output = env.tick(&["submit", "--from", "2024-05-01", "--to", "2024-05-07", "--yes"]);
+    assert!(output.status.success(), "{}", stdout(&output));
+
+    let summary = stdout(&output);
+    assert!(summary.contains("2024-05-03: Submitted 2 entries"), "{}", summary);
+    assert!(summary.contains("2024-05-06: Submitted 1 entries"), "{}", summary);
+    assert!(!summary.contains("2024-05-02"), "{}", summary);
+    assert_eq!(3, env.server.state().entries.len());
+    assert_eq!(true, env.read_day("2024-05-03")["all_submitted"]);
+    assert_eq!(false, env.read_day("2024-05-13")["all_submitted"]);
+
+    // Files that aren't days are left alone.
+    let month_dir = env.day_path("2024-05-13").parent().unwrap().to_path_buf();
+    fs::write(month_dir.join(".DS_Store"), "").unwrap();
+    fs::write(month_dir.join("notes.txt"), "").unwrap();
+
+    let output = env.tick(&["submit", "--all-pending", "--yes"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("2024-05-13: Submitted 1 entries"));
+    assert_eq!(4, env.server.state().entries.len());
+}
+
+#[test]
+fn test_submit_dry_run() {
+    let env = TestEnv::logged_in();
+    let mut updated = submitted_entry(1002, "10:00:00", "11:00:00", "Planning");
+    updated["updated_at"] = json!("2024-05-03T18:00:00");
+    let mut lunch = entry("Acme", 11, "Development", "12:00:00", Some("12:30:00"));
+    lunch["kind"] = json!("break");
+    env.write_day("2024-05-03", json!({
+        "entries": [
+            submitted_entry(1001, "09:00:00", "10:00:00", "Standup"),
+            updated,
+            entry("Acme", 11, "Development", "11:00:00", Some("12:00:00")),
+            lunch,
+        ],
+        "all_submitted": false,
+    }));
+
+    let output = env.tick(&["submit", "--from", "2024-05-03", "--dry-run"]);
+    assert!(output.status.success(), "{}", stdout(&output));
+
+    let lines = stdout_lines(&output);
+    assert!(lines.contains(&"skip 2024-05-03 Acme | Development 1.00 hours Standup".to_string()), "{:?}", lines);
+    assert!(lines.contains(&"update 2024-05-03 Acme | Development 1.00 hours Planning".to_string()), "{:?}", lines);
+    assert!(lines.contains(&"create 2024-05-03 Acme | Development 1.00 hours".to_string()), "{:?}", lines);
+    assert!(lines.contains(&"excluded 2024-05-03 Acme | Development 0.50 hours".to_string()), "{:?}", lines);
+    assert!(lines.contains(&"Total: 2.00 hours".to_string()), "{:?}", lines);
+
+    let requests = env.server.requests();
+    assert!(requests.iter().all(|r| r.method == "GET"));
+    assert_eq!(false, env.read_day("2024-05-03")["all_submitted"]);
+
+    // Problems and missing end times don't keep a dry run from showing the plan.
+    env.write_day("2024-05-06", json!({
+        "entries": [
+            entry("Acme", 11, "Development", "09:00:00", Some("10:30:00")),
+            entry("Acme", 12, "Meetings", "10:00:00", None),
+        ],
+        "all_submitted": false,
+    }));
+
+    let output = env.tick(&["submit", "--from", "2024-05-06", "--dry-run"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("Warning:"));
+    assert!(stdout(&output).contains("You didn't set an end time"));
+
+    let lines = stdout_lines(&output);
+    assert!(lines.contains(&"create 2024-05-06 Acme | Development 1.50 hours".to_string()), "{:?}", lines);
+}
+
+#[test]
+fn test_retry_unavailable_server() {
+    let env = TestEnv::logged_in();
+    env.write_config(&(env.read_config() + "\n[retry]\ninitial_delay_ms = 10\n"));
+    env.server.state().entries = vec![
+        json!({ "id": 1000, "date": "2024-05-03", "task_id": 11, "hours": 1.5, "notes": "", "user_id": 7 }),
+    ];
+    env.server.state().failures = vec![(503, Some(0)), (502, None)];
+
+    let output = env.tick(&["pull", "--from", "2024-05-03"]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(stdout(&output).contains("Pulled 1 entries"));
+
+    env.server.state().failures = vec![(500, None); 4];
+
+    let output = env.tick(&["pull", "--from", "2024-05-03"]);
+    assert_eq!(Some(4), output.status.code());
+    assert!(stderr(&output).contains("Tickspot responded with status 500"), "{}", stderr(&output));
+    assert!(!stderr(&output).contains("panicked"), "{}", stderr(&output));
+}
+
+#[test]
+fn test_retry_rate_limited_submit() {
+    let env = TestEnv::logged_in();
+    env.write_config(&(env.read_config() + "\n[retry]\ninitial_delay_ms = 10\n"));
+    env.write_day("2024-05-03", json!({
+        "entries": [entry("Acme", 11, "Development", "09:00:00", Some("10:30:00"))],
+        "all_submitted": false,
+    }));
+    env.server.state().failures = vec![(429, Some(0))];
+
+    let output = env.tick(&["submit", "--from", "2024-05-03", "--yes"]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert_eq!(1, env.server.state().entries.len());
+
+    // A failed create may have been handled, so it's not sent again.
+    env.write_day("2024-05-06", json!({
+        "entries": [entry("Acme", 11, "Development", "09:00:00", Some("10:30:00"))],
+        "all_submitted": false,
+    }));
+    env.server.state().failures = vec![(500, None)];
+
+    let output = env.tick(&["submit", "--from", "2024-05-06", "--yes"]);
+    assert_eq!(Some(4), output.status.code());
+    assert!(stdout(&output).contains("Couldn't send the following entry"));
+    assert_eq!(false, env.read_day("2024-05-06")["all_submitted"]);
+    assert_eq!(1, env.server.state().entries.len());
+}
+
+#[test]
+fn test_exit_codes() {
+    let env = TestEnv::logged_in();
+    env.server.state().roles[0]["api_token"] = json!("another-token");
+
+    let output = env.tick(&["pull", "--from", "2024-05-03"]);
+    assert_eq!(Some(3), output.status.code());
+
+    let env = TestEnv::logged_in();
+    env.write_config(&(env.read_config().replace(&env.server.url(), "http://127.0.0.1:1") + "\n[retry]\ninitial_delay_ms = 10\n"));
+
+    let output = env.tick(&["pull", "--from", "2024-05-03"]);
+    assert_eq!(Some(4), output.status.code());
+    assert!(stderr(&output).contains("Error connecting to Tickspot"));
+
+    let env = TestEnv::logged_in();
+    env.write_day("2024-05-03", json!({
+        "entries": [
+            entry("Acme", 11, "Development", "09:00:00", Some("10:30:00")),
+            entry("Acme", 12, "Meetings", "10:00:00", Some("11:00:00")),
+        ],
+        "all_submitted": false,
+    }));
+
+    let output = env.tick(&["submit", "--from", "2024-05-03", "--yes"]);
+    assert_eq!(Some(5), output.status.code());
+    assert!(stderr(&output).contains("tick submit --force"));
+
+    fs::write(env.day_path("2024-05-03"), "{ not json").unwrap();
+
+    let output = env.tick(&["check", "2024-05-03"]);
+    assert_eq!(Some(7), output.status.code());
+    assert!(!stderr(&output).contains("panicked"), "{}", stderr(&output));
+}
+
+#[test]
+fn test_api_key_from_credential_store() {
+    let env = TestEnv::logged_in();
+    let config = format!(
+        "version = 0\nsubscription_id = {}\nbase_url = '{}'\n",
+        mock_server::SUBSCRIPTION_ID,
+        env.server.url(),
+    );
+
+    env.write_config(&format!("{}credential_store = 'command'\napi_key_command = 'echo {}'\n", config, mock_server::API_TOKEN));
+    let output = env.tick(&["pull", "--from", "2024-05-03"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    env.write_config(&format!(
+        "{}credential_store = 'command'\napi_key_command = 'echo {}'\n\n[[roles]]\nsubscription_id = {}\ncompany = 'Acme'\napi_token = 'old-token'\n",
+        config,
+        mock_server::API_TOKEN,
+        mock_server::SUBSCRIPTION_ID,
+    ));
+    let output = env.tick(&["rounding", "set", "nearest", "15"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!env.read_config().contains("old-token"));
+
+    env.write_config(&format!("{}credential_store = 'command'\napi_key_command = 'exit 1'\n", config));
+    let output = env.tick(&["pull", "--from", "2024-05-03"]);
+    assert_eq!(Some(3), output.status.code());
+    assert!(stderr(&output).contains("exit 1"));
+
+    env.write_config(&format!("{}credential_store = 'env'\n", config));
+    let output = env.tick(&["pull", "--from", "2024-05-03"]);
+    assert_eq!(Some(3), output.status.code());
+
+    let output = env
+        .command()
+        .env("TICK_API_TOKEN", mock_server::API_TOKEN)
+        .args(["pull", "--from", "2024-05-03"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+}
+
+#[test]
+fn test_configuration_from_environment() {
+    let env = TestEnv::new();
+    env.server.state().entries = vec![
+        json!({ "id": 1000, "date": "2024-05-03", "task_id": 11, "hours": 1.5, "notes": "From the web", "user_id": 7 }),
+    ];
+    let data_dir = env.home().join("entries");
+    let tick = |args: &[&str]| {
+        env.command()
+            .env("TICK_API_TOKEN", mock_server::API_TOKEN)
+            .env("TICK_SUBSCRIPTION_ID", mock_server::SUBSCRIPTION_ID.to_string())
+            .env("TICK_BASE_URL", env.server.url())
+            .env("TICK_DATA_DIR", &data_dir)
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = tick(&["pull", "--from", "2024-05-01", "--to", "2024-05-05"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(data_dir.join("2024/05/2024-05-03.json").exists());
+    assert!(!env.read_config().contains(mock_server::API_TOKEN));
+
+    let output = tick(&["report", "--from", "2024-05-01", "--to", "2024-05-05"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("1.50"));
+
+    env.tick(&["profile", "add", "work"]);
+    let output = tick(&["--profile", "work", "report", "--from", "2024-05-01", "--to", "2024-05-05"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!stdout(&output).contains("1.50"));
+
+    let output = tick(&["--profile", "work", "pull", "--from", "2024-05-01", "--to", "2024-05-05"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(env.home().join("entries-work/2024/05/2024-05-03.json").exists());
+
+    let output = env
+        .command()
+        .env("TICK_API_TOKEN", mock_server::API_TOKEN)
+        .env("TICK_SUBSCRIPTION_ID", "acme")
+        .env("TICK_BASE_URL", env.server.url())
+        .args(["pull", "--from", "2024-05-03"])
+        .output()
+        .unwrap();
+    assert_eq!(Some(3), output.status.code());
+    assert!(stderr(&output).contains("TICK_SUBSCRIPTION_ID"));
+
+    let output = env
+        .command()
+        .env("TICK_SUBSCRIPTION_ID", "acme")
+        .args(["profile", "list"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+}
+
+#[test]
+fn test_rounded_report() {
+    let env = TestEnv::logged_in();
+    env.write_day("2024-05-03", json!({
+        "entries": [
+            entry("Acme", 11, "Development", "09:00:00", Some("09:17:00")),
+            entry("Globex Website", 21, "Design", "09:17:00", Some("10:00:00")),
+        ],
+        "all_submitted": false,
+    }));
+
+    let output = env.tick(&["rounding", "set", "down", "15", "--project", "acme"]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    let output = env.tick(&["rounding", "set", "nearest", "30"]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(!env.tick(&["rounding", "set", "nearest", "7"]).status.success());
+
+    let output = env.tick(&["rounding", "list"]);
+    assert!(stdout(&output).contains("All projects: to the nearest 30 minutes"));
+    assert!(stdout(&output).contains("Acme: down to 15 minutes"));
+
+    let output = env.tick(&["report", "--from", "2024-05-03"]);
+    let report = stdout(&output);
+    assert!(report.contains("Acme               0.25     0.28"), "{}", report);
+    assert!(report.contains("Globex Website     0.50     0.72"), "{}", report);
+}
+
+fn entry(project: &str, task_id: u32, task: &str, start_time: &str, end_time: Option<&str>) -> serde_json::Value {
+    json!({
+        "tick_id": null,
+        "project_name": project,
+        "task_id": task_id,
+        "task_name": task,
+        "start_time": start_time,
+        "end_time": end_time,
+        "notes": "",
+        "submitted_at": null,
+        "updated_at": null,
+    })
+}
+
+fn submitted_entry(tick_id: u32, start_time: &str, end_time: &str, notes: &str) -> serde_json::Value {
+    json!({
+        "tick_id": tick_id,
+        "project_name": "Acme",
+        "task_id": 11,
+        "task_name": "Development",
+        "start_time": start_time,
+        "end_time": end_time,
+        "notes": notes,
+        "submitted_at": "2024-05-03T17:00:00",
+        "updated_at": null,
+    })
+}
@@ -0,0 +1,356 @@
+//! A minimal in-process stand-in for the Tickspot v2 API, covering the
+//! endpoints used by `api.rs`. It speaks just enough HTTP/1.1 for reqwest.
+
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use serde_json::{json, Value};
+
+pub const SUBSCRIPTION_ID: u32 = 1234;
+pub const COMPANY: &str = "Acme Inc";
+pub const API_TOKEN: &str = "token-1234";
+pub const OTHER_SUBSCRIPTION_ID: u32 = 5678;
+pub const OTHER_COMPANY: &str = "Globex Corp";
+pub const OTHER_API_TOKEN: &str = "token-5678";
+pub const EMAIL: &str = "jane@example.com";
+pub const PASSWORD: &str = "secret";
+pub const PAGE_SIZE: usize = 100;
+
+#[derive(Clone, Debug)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: String,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+pub struct State {
+    pub roles: Vec<Value>,
+    pub users: Vec<Value>,
+    pub projects: Vec<Value>,
+    pub tasks: HashMap<u32, Vec<Value>>,
+    pub entries: Vec<Value>,
+    pub requests: Vec<Request>,
+    /// Statuses to answer the next requests with, before handling them, with
+    /// an optional Retry-After in seconds.
+    pub failures: Vec<(u16, Option<u32>)>,
+    /// Answers every page with the first, like a proxy dropping the query.
+    pub ignore_page: bool,
+    next_entry_id: u32,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        let mut tasks = HashMap::new();
+        tasks.insert(1, vec![
+            json!({ "id": 11, "name": "Development", "project_id": 1 }),
+            json!({ "id": 12, "name": "Meetings", "project_id": 1 }),
+        ]);
+        tasks.insert(2, vec![
+            json!({ "id": 21, "name": "Design", "project_id": 2 }),
+        ]);
+        tasks.insert(3, vec![
+            json!({ "id": 31, "name": "Development", "project_id": 3 }),
+        ]);
+
+        Self {
+            roles: vec![
+                json!({
+                    "subscription_id": SUBSCRIPTION_ID,
+                    "company": COMPANY,
+                    "api_token": API_TOKEN,
+                }),
+                json!({
+                    "subscription_id": OTHER_SUBSCRIPTION_ID,
+                    "company": OTHER_COMPANY,
+                    "api_token": OTHER_API_TOKEN,
+                }),
+            ],
+            users: vec![json!({ "id": 7, "first_name": "Jane", "last_name": "Doe" })],
+            projects: vec![
+                json!({ "id": 1, "name": "Acme" }),
+                json!({ "id": 2, "name": "Globex Website" }),
+                json!({ "id": 3, "name": "Globex App" }),
+            ],
+            tasks,
+            entries: vec![],
+            requests: vec![],
+            failures: vec![],
+            ignore_page: false,
+            next_entry_id: 1000,
+        }
+    }
+}
+
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockServer {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Cannot bind mock server");
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let thread_state = Arc::clone(&state);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                handle_connection(stream, &thread_state);
+            }
+        });
+
+        Self { addr, state }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.state().requests.clone()
+    }
+}
+
+fn handle_connection(stream: TcpStream, state: &Arc<Mutex<State>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() || request_line.is_empty() {
+        return;
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length: usize = headers
+        .get("content-length")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).unwrap();
+
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path.to_string(), query.to_string()),
+        None => (target.clone(), String::new()),
+    };
+    let request = Request {
+        method,
+        path,
+        query,
+        headers: headers.clone(),
+        body: String::from_utf8(body).unwrap(),
+    };
+
+    let (status, body) = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(request.clone());
+
+        if !state.failures.is_empty() {
+            let (status, retry_after) = state.failures.remove(0);
+            drop(state);
+            let header = retry_after.map(|seconds| format!("Retry-After: {}\r\n", seconds));
+            return write_response(stream, status, None, header);
+        }
+
+        route(&mut state, &request, headers.get("authorization"))
+    };
+
+    // Like Tickspot, tag GET responses so clients can revalidate them.
+    let etag = match (request.method.as_str(), status, &body) {
+        ("GET", 200, Some(body)) => Some(etag(body)),
+        _ => None,
+    };
+    if etag.is_some() && etag.as_ref() == headers.get("if-none-match") {
+        return write_response(stream, 304, None, etag.map(|e| format!("ETag: {}\r\n", e)));
+    }
+
+    write_response(stream, status, body, etag.map(|e| format!("ETag: {}\r\n", e)));
+}
+
+fn route(state: &mut State, request: &Request, auth: Option<&String>) -> (u16, Option<Value>) {
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    let page = query_param(&request.query, "page")
+        .and_then(|p| p.parse::<usize>().ok())
+        .filter(|_| !state.ignore_page)
+        .unwrap_or(1);
+
+    if segments == ["api", "v2", "roles.json"] {
+        let expected = format!("Basic {}", base64(&format!("{}:{}", EMAIL, PASSWORD)));
+        if auth != Some(&expected) {
+            return unauthorized();
+        }
+        return (200, Some(Value::Array(state.roles.clone())));
+    }
+
+    if segments.len() < 4 || segments[1] != "api" || segments[2] != "v2" {
+        return (404, None);
+    }
+
+    // A token only works for the subscription of its role.
+    let authorized = state.roles.iter().any(|r| {
+        r["subscription_id"].as_u64() == segments[0].parse().ok()
+            && auth == Some(&format!("Bearer {}", r["api_token"].as_str().unwrap()))
+    });
+    if !authorized {
+        return unauthorized();
+    }
+
+    match (request.method.as_str(), &segments[3..]) {
+        ("GET", ["users.json"]) => (200, Some(Value::Array(state.users.clone()))),
+        ("GET", ["projects.json"]) => (200, Some(paginate(&state.projects, page))),
+        ("GET", ["projects", id, "tasks.json"]) => {
+            let id: u32 = id.parse().unwrap_or(0);
+            let tasks = state.tasks.get(&id).cloned().unwrap_or_default();
+            (200, Some(paginate(&tasks, page)))
+        }
+        ("GET", ["tasks", file]) => {
+            let id: u64 = file.trim_end_matches(".json").parse().unwrap_or(0);
+            match state.tasks.values().flatten().find(|t| t["id"].as_u64() == Some(id)) {
+                Some(task) => (200, Some(task.clone())),
+                None => (404, None),
+            }
+        }
+        ("GET", ["entries.json"]) => {
+            let param = |name| query_param(&request.query, name).unwrap_or_default();
+            let (from, to, user_id) = (param("start_date"), param("end_date"), param("user_id"));
+            let entries: Vec<Value> = state
+                .entries
+                .iter()
+                .filter(|e| {
+                    let date = e["date"].as_str().unwrap_or_default();
+                    date >= from && date <= to && e["user_id"].as_u64() == user_id.parse().ok()
+                })
+                .cloned()
+                .collect();
+            (200, Some(paginate(&entries, page)))
+        }
+        ("POST", ["entries.json"]) => {
+            let mut entry: Value = serde_json::from_str(&request.body).unwrap();
+            entry["id"] = json!(state.next_entry_id);
+            entry["user_id"] = state.users[0]["id"].clone();
+            state.next_entry_id += 1;
+            state.entries.push(entry.clone());
+            (201, Some(entry))
+        }
+        ("PUT", ["entries", file]) => {
+            let id: u64 = file.trim_end_matches(".json").parse().unwrap_or(0);
+            let update: Value = serde_json::from_str(&request.body).unwrap();
+            match state.entries.iter_mut().find(|e| e["id"].as_u64() == Some(id)) {
+                Some(entry) => {
+                    for key in ["date", "task_id", "hours", "notes"] {
+                        entry[key] = update[key].clone();
+                    }
+                    (200, Some(entry.clone()))
+                }
+                None => (404, None),
+            }
+        }
+        ("DELETE", ["entries", file]) => {
+            let id: u64 = file.trim_end_matches(".json").parse().unwrap_or(0);
+            let count = state.entries.len();
+            state.entries.retain(|e| e["id"].as_u64() != Some(id));
+            if state.entries.len() < count { (204, None) } else { (404, None) }
+        }
+        _ => (404, None),
+    }
+}
+
+fn paginate(items: &[Value], page: usize) -> Value {
+    let items = items
+        .iter()
+        .skip((page.max(1) - 1) * PAGE_SIZE)
+        .take(PAGE_SIZE)
+        .cloned()
+        .collect();
+
+    Value::Array(items)
+}
+
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+fn unauthorized() -> (u16, Option<Value>) {
+    (401, Some(json!("Authentication failed")))
+}
+
+fn write_response(mut stream: TcpStream, status: u16, body: Option<Value>, header: Option<String>) {
+    let body = body.map(|b| b.to_string()).unwrap_or_default();
+    let reason = match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        304 => "Not Modified",
+        401 => "Unauthorized",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        _ => "Not Found",
+    };
+    let header = header.unwrap_or_default();
+
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        header,
+        body.len(),
+        body,
+    );
+    let _ = stream.write_all(response.as_bytes());
+}
+
+fn etag(body: &Value) -> String {
+    let mut hasher = DefaultHasher::new();
+    body.to_string().hash(&mut hasher);
+
+    format!("\"{:x}\"", hasher.finish())
+}
+
+fn base64(input: &str) -> String {
+    const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let bytes = input.as_bytes();
+    let mut output = String::new();
+
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                output.push(CHARS[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+
+    output
+}
@@ -0,0 +1,199 @@
+//! Helpers for running the `tick` binary end-to-end against the mock
+//! Tickspot server, with all of its files kept in a throwaway home dir.
+
+#![allow(dead_code)]
+
+pub mod mock_server;
+
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use serde_json::Value;
+
+pub use mock_server::MockServer;
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+pub struct TestEnv {
+    home: PathBuf,
+    pub server: MockServer,
+}
+
+impl TestEnv {
+    pub fn new() -> Self {
+        let home = std::env::temp_dir().join(format!(
+            "tick-cli-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst),
+        ));
+        let _ = fs::remove_dir_all(&home);
+        fs::create_dir_all(home.join(".config")).unwrap();
+
+        // Without this file `dirs` can't find a documents dir on Linux.
+        fs::write(
+            home.join(".config/user-dirs.dirs"),
+            "XDG_DOCUMENTS_DIR=\"$HOME/Documents\"\n",
+        )
+        .unwrap();
+
+        Self { home, server: MockServer::start() }
+    }
+
+    /// A test env with a config that's logged in to the mock server.
+    pub fn logged_in() -> Self {
+        let env = Self::new();
+        env.write_config(&format!(
+            "version = 0\nsubscription_id = {}\napi_key = '{}'\nbase_url = '{}'\n",
+            mock_server::SUBSCRIPTION_ID,
+            mock_server::API_TOKEN,
+            env.server.url(),
+        ));
+
+        env
+    }
+
+    pub fn home(&self) -> &Path {
+        &self.home
+    }
+
+    pub fn config_path(&self) -> PathBuf {
+        self.home.join(".config/tick-cli/default-config.toml")
+    }
+
+    pub fn write_config(&self, content: &str) {
+        let path = self.config_path();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    pub fn read_config(&self) -> String {
+        fs::read_to_string(self.config_path()).unwrap()
+    }
+
+    pub fn documents_dir(&self) -> PathBuf {
+        self.home.join("Documents/Tick")
+    }
+
+    pub fn day_path(&self, date: &str) -> PathBuf {
+        let mut path = self.documents_dir();
+        path.push(&date[..4]);
+        path.push(&date[5..7]);
+        path.push(format!("{}.json", date));
+
+        path
+    }
+
+    pub fn read_day(&self, date: &str) -> Value {
+        serde_json::from_str(&fs::read_to_string(self.day_path(date)).unwrap()).unwrap()
+    }
+
+    pub fn write_day(&self, date: &str, entries: Value) {
+        let path = self.day_path(date);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, serde_json::to_string_pretty(&entries).unwrap()).unwrap();
+    }
+
+    pub fn command(&self) -> Command {
+        self.with_env(Command::new(env!("CARGO_BIN_EXE_tick-cli")))
+    }
+
+    fn with_env(&self, mut command: Command) -> Command {
+        command
+            .env_clear()
+            .env("PATH", std::env::var("PATH").unwrap_or_default())
+            .env("HOME", &self.home)
+            .env("XDG_CONFIG_HOME", self.home.join(".config"))
+            .env("XDG_CACHE_HOME", self.home.join(".cache"))
+            .env("XDG_DATA_HOME", self.home.join(".local/share"));
+
+        command
+    }
+
+    pub fn tick(&self, args: &[&str]) -> Output {
+        self.command().args(args).output().expect("Cannot run tick")
+    }
+
+    /// Runs tick in a terminal through util-linux's `script`, so its prompts
+    /// can be answered. Each answer is typed once its prompt shows up, as
+    /// the password prompt drops whatever was typed before it. Returns the
+    /// exit code and everything the terminal showed.
+    pub fn tick_in_terminal(&self, args: &[&str], answers: &[(&str, &str)]) -> (Option<i32>, String) {
+        let command_line = std::iter::once(env!("CARGO_BIN_EXE_tick-cli"))
+            .chain(args.iter().copied())
+            .map(|arg| format!("'{}'", arg))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut child = self
+            .with_env(Command::new("script"))
+            .env("TERM", "xterm")
+            .args(["-qec", &command_line, "/dev/null"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Cannot run script");
+
+        let shown = Arc::new(Mutex::new(String::new()));
+        let mut stdout = child.stdout.take().unwrap();
+        let reader_shown = Arc::clone(&shown);
+        let reader = thread::spawn(move || {
+            let mut buffer = [0; 1024];
+            while let Ok(read) = stdout.read(&mut buffer) {
+                if read == 0 {
+                    break;
+                }
+                reader_shown.lock().unwrap().push_str(&String::from_utf8_lossy(&buffer[..read]));
+            }
+        });
+
+        let mut stdin = child.stdin.take().unwrap();
+        let mut seen = 0;
+        for (prompt, keys) in answers {
+            let deadline = Instant::now() + Duration::from_secs(10);
+            loop {
+                if let Some(index) = shown.lock().unwrap()[seen..].find(prompt) {
+                    seen += index + prompt.len();
+                    break;
+                }
+                assert!(Instant::now() < deadline, "No {} prompt in:\n{}", prompt, shown.lock().unwrap());
+                thread::sleep(Duration::from_millis(20));
+            }
+            stdin.write_all(keys.as_bytes()).unwrap();
+            stdin.flush().unwrap();
+        }
+
+        let status = child.wait().unwrap();
+        drop(stdin);
+        reader.join().unwrap();
+
+        let shown = shown.lock().unwrap().clone();
+        (status.code(), shown)
+    }
+}
+
+impl Drop for TestEnv {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.home);
+    }
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+/// The lines of stdout with the padding between columns squeezed to one
+/// space, so tables can be checked line by line.
+pub fn stdout_lines(output: &Output) -> Vec<String> {
+    stdout(output)
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect()
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}